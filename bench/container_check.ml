@@ -0,0 +1,35 @@
+(** Person container check - OCaml side
+
+    Reads a Person container with avro-simple's Container_reader and checks
+    that record [i] is [create_person i], like [avro-rust-bench verify]. Used
+    on files other writers produced, e.g. after [avro-rust-bench append]:
+
+    {v
+      avro-rust-bench append 10000 deflate --output appended.avro
+      dune exec bench/container_check.exe -- appended.avro
+    v}
+*)
+open Avro_simple
+open Person
+
+let () =
+  if Array.length Sys.argv < 2 then begin
+    prerr_endline "Usage: container_check <container.avro>";
+    exit 2
+  end;
+  let path = Sys.argv.(1) in
+  Avro.init_codecs ();
+  let reader = Container_reader.open_file ~path ~codec:person_codec () in
+  let records, mismatches =
+    Container_reader.fold (fun (i, mismatches) p ->
+      if p = create_person i then (i + 1, mismatches)
+      else begin
+        if mismatches < 10 then Printf.printf "Mismatch at record %d\n" i;
+        (i + 1, mismatches + 1)
+      end)
+      (0, 0) reader
+  in
+  let codec = Container_reader.codec_name reader in
+  Container_reader.close reader;
+  Printf.printf "%s (%s): %d records read, %d mismatched\n" path codec records mismatches;
+  if mismatches > 0 then exit 1
@@ -79,7 +79,8 @@ let benchmark_container count compression =
   let elapsed_read = Unix.gettimeofday () -. start_read in
 
   let file_size = (Unix.stat filename).Unix.st_size in
-  Sys.remove filename;
+  (* Set AVRO_BENCH_KEEP_FILES to keep the file for avro-rust-bench verify *)
+  if Sys.getenv_opt "AVRO_BENCH_KEEP_FILES" = None then Sys.remove filename;
 
   Printf.printf "Container[%s]: Wrote %d records in %.6f seconds, Read in %.6f seconds (%d bytes)\n"
     compression count elapsed_write elapsed_read file_size
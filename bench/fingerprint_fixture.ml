@@ -0,0 +1,74 @@
+(** Schema fingerprint fixture - OCaml side
+
+    Prints the Parsing Canonical Form that avro-simple computes for every
+    .avsc file in a directory, with its CRC-64-AVRO (Rabin) fingerprint and
+    MD5, as the JSON fixture [avro-rust-bench fingerprint <dir> <fixture.json>]
+    compares against:
+
+    {v
+      dune exec bench/fingerprint_fixture.exe -- bench/schemas > ocaml.json
+      avro-rust-bench fingerprint bench/schemas ocaml.json
+    v}
+*)
+open Avro_simple
+
+let read_file path =
+  let ic = open_in_bin path in
+  let contents = really_input_string ic (in_channel_length ic) in
+  close_in ic;
+  contents
+
+(* CRC-64-AVRO as the specification defines it: the fingerprint of the empty
+   string is both the initial value and the polynomial. [Fingerprint.crc64]
+   (and [Fingerprint.rabin_fingerprint], its alias) starts from all ones with
+   another polynomial, so it is reported as avro_simple_crc64 and the Rust
+   side does not compare it. *)
+let rabin_empty = 0xc15d213aa4d7a795L
+
+let rabin_table =
+  Array.init 256 (fun i ->
+    let fp = ref (Int64.of_int i) in
+    for _ = 1 to 8 do
+      let mask = Int64.neg (Int64.logand !fp 1L) in
+      fp := Int64.(logxor (shift_right_logical !fp 1) (logand rabin_empty mask))
+    done;
+    !fp)
+
+let rabin_fingerprint canonical_form =
+  let fp = ref rabin_empty in
+  String.iter (fun c ->
+    let index = Int64.(to_int (logand (logxor !fp (of_int (Char.code c))) 0xffL)) in
+    fp := Int64.(logxor (shift_right_logical !fp 8) rabin_table.(index)))
+    canonical_form;
+  !fp
+
+let entry dir file =
+  match Schema_json.of_string (read_file (Filename.concat dir file)) with
+  | Ok schema ->
+    let canonical_form = Fingerprint.to_canonical_json schema in
+    `Assoc [
+      ("file", `String file);
+      ("canonical_form", `String canonical_form);
+      ("rabin_fingerprint",
+       `String (Printf.sprintf "%016Lx" (rabin_fingerprint canonical_form)));
+      ("md5", `String (Digest.to_hex (Digest.string canonical_form)));
+      ("avro_simple_crc64",
+       `String (Printf.sprintf "%016Lx" (Fingerprint.crc64 schema)));
+    ]
+  | Error msg ->
+    Printf.eprintf "%s: %s\n" file msg;
+    exit 1
+
+let () =
+  if Array.length Sys.argv < 2 then begin
+    prerr_endline "Usage: fingerprint_fixture <schema-dir>";
+    exit 2
+  end;
+  let dir = Sys.argv.(1) in
+  let files =
+    Sys.readdir dir
+    |> Array.to_list
+    |> List.filter (fun file -> Filename.check_suffix file ".avsc")
+    |> List.sort compare
+  in
+  print_endline (Yojson.Basic.pretty_to_string (`List (List.map (entry dir) files)))
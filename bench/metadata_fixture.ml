@@ -0,0 +1,62 @@
+(** Container user metadata fixture - OCaml side
+
+    Writes a Person container whose header carries the user metadata entries
+    [avro-rust-bench metadata] expects, or checks a file the Rust bench wrote:
+
+    {v
+      dune exec bench/metadata_fixture.exe -- write ocaml.avro
+      avro-rust-bench metadata --input ocaml.avro
+
+      avro-rust-bench metadata --output rust.avro
+      dune exec bench/metadata_fixture.exe -- check rust.avro
+    v}
+
+    [write-reserved] adds an [avro.lineage] entry, which Container_writer
+    accepts but the Rust bench must reject.
+*)
+open Avro_simple
+open Person
+
+(* Same entries as expected_entries in bench/rust/src/metadata.rs *)
+let entries = [
+  ("lineage.pipeline", "people-daily");
+  ("lineage.upstream", "warehouse/people/2024-01-01/part-0000.avro");
+  ("lineage.note", "Z\xc3\xbcrich \xe2\x86\x92 \xe6\x9d\xb1\xe4\xba\xac");
+  ("binary.all-bytes", String.init 256 Char.chr);
+  ("binary.empty", "");
+  ("binary.4k", String.init 4096 (fun i -> Char.chr (i * 31 mod 256)));
+]
+
+let write path metadata =
+  Avro.init_codecs ();
+  let writer = Container_writer.create ~path ~codec:person_codec ~metadata () in
+  for i = 0 to 999 do Container_writer.write writer (create_person i) done;
+  Container_writer.close writer
+
+let check path =
+  Avro.init_codecs ();
+  let reader = Container_reader.open_file ~path ~codec:person_codec () in
+  let metadata = Container_reader.metadata reader in
+  Container_reader.close reader;
+  let problems =
+    List.fold_left (fun problems (key, value) ->
+      match List.assoc_opt key metadata with
+      | Some found when found = value ->
+        Printf.printf "  ok       %s\n" key; problems
+      | Some _ -> Printf.printf "  DIFFERS  %s\n" key; problems + 1
+      | None -> Printf.printf "  MISSING  %s\n" key; problems + 1)
+      0 entries
+  in
+  if problems > 0 then begin
+    Printf.eprintf "%d metadata problems in %s\n" problems path;
+    exit 1
+  end
+
+let () =
+  match Array.to_list Sys.argv with
+  | [_; "write"; path] -> write path entries
+  | [_; "write-reserved"; path] -> write path (("avro.lineage", "people-daily") :: entries)
+  | [_; "check"; path] -> check path
+  | _ ->
+    prerr_endline "Usage: metadata_fixture (write|write-reserved|check) <file.avro>";
+    exit 2
@@ -0,0 +1,31 @@
+(** Person records shared by the fixture and check executables
+
+    The same schema and [create_person] as the benchmarks and
+    [avro_rust_bench_common::create_person], so files written by any side
+    compare record for record.
+*)
+open Avro_simple
+
+type person = {
+  name: string;
+  age: int;
+  email: string option;
+  phone_numbers: string array;
+}
+
+let create_person i =
+  {
+    name = Printf.sprintf "Person_%d" i;
+    age = 20 + (i mod 60);
+    email = if i mod 3 = 0 then Some (Printf.sprintf "person%d@example.com" i) else None;
+    phone_numbers = Array.init (1 + i mod 3) (fun j -> Printf.sprintf "+1-555-%04d" (i * 10 + j));
+  }
+
+let person_codec =
+  Codec.record (Type_name.simple "Person")
+    (fun name age email phone_numbers -> ({ name; age; email; phone_numbers } : person))
+  |> Codec.field "name" Codec.string (fun (p : person) -> p.name)
+  |> Codec.field "age" Codec.int (fun (p : person) -> p.age)
+  |> Codec.field_opt "email" Codec.string (fun (p : person) -> p.email)
+  |> Codec.field "phone_numbers" (Codec.array Codec.string) (fun (p : person) -> p.phone_numbers)
+  |> Codec.finish
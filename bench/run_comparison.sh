@@ -81,31 +81,15 @@ build_java() {
 }
 
 build_rust() {
-    print_info "Building Rust benchmarks (apache-avro)..."
-    cd rust
+    print_info "Building Rust benchmarks (apache-avro, serde_avro_fast)..."
     if [ ! -f "Cargo.toml" ]; then
-        print_error "Cargo.toml not found in rust/"
+        print_error "Cargo.toml not found in bench/"
         return 1
     fi
-    cargo build --release --quiet || {
+    cargo build --release --quiet --workspace || {
         print_error "Cargo build failed"
         return 1
     }
-    cd "$BENCH_DIR"
-}
-
-build_rust_fast() {
-    print_info "Building Rust benchmarks (serde_avro_fast)..."
-    cd rust-fast
-    if [ ! -f "Cargo.toml" ]; then
-        print_error "Cargo.toml not found in rust-fast/"
-        return 1
-    fi
-    cargo build --release --quiet || {
-        print_error "Cargo build failed"
-        return 1
-    }
-    cd "$BENCH_DIR"
 }
 
 setup_python() {
@@ -177,13 +161,13 @@ run_full_comparison() {
     local ocaml_avro_cmd="../_build/default/bench/ocaml-avro-codegen/bench.exe $op $cnt"
     local java_coldstart_cmd="java -jar java/target/avro-java-bench-1.0-SNAPSHOT.jar $op $cnt $comp"
     local java_warmup_cmd="java -jar java/target/avro-java-bench-1.0-SNAPSHOT.jar $op $cnt $comp --warmup"
-    local rust_apache_cmd="rust/target/release/avro-rust-bench $op $cnt $comp"
-    local rust_fast_cmd="rust-fast/target/release/avro-rust-bench-fast $op $cnt $comp"
+    local rust_apache_cmd="target/release/avro-rust-bench $op $cnt $comp"
+    local rust_fast_cmd="target/release/avro-rust-bench-fast $op $cnt $comp"
     local python_fastavro_cmd="python/venv/bin/python python/avro_python_bench.py $op $cnt $comp"
     local python_avro_cmd="python-avro/venv/bin/python python-avro/avro_python_bench.py $op $cnt $comp"
 
     # Run hyperfine comparison
-    # Note: serde_avro_fast and ocaml-avro don't support container operations
+    # Note: ocaml-avro doesn't support container operations
     if [ "$op" = "container" ]; then
         hyperfine \
             --warmup "$WARMUP" \
@@ -194,6 +178,7 @@ run_full_comparison() {
             --command-name "Java (coldstart)" "$java_coldstart_cmd" \
             --command-name "Java (warmed up)" "$java_warmup_cmd" \
             --command-name "Rust (apache-avro)" "$rust_apache_cmd" \
+            --command-name "Rust (serde_avro_fast)" "$rust_fast_cmd" \
             --command-name "Python (fastavro)" "$python_fastavro_cmd" \
             --command-name "Python (avro)" "$python_avro_cmd"
     else
@@ -348,7 +333,6 @@ case "${COMMAND:-}" in
         build_ocaml_avro
         build_java
         build_rust
-        build_rust_fast
         setup_python
         setup_python_avro
         print_info "All benchmarks built successfully!"
@@ -372,8 +356,7 @@ case "${COMMAND:-}" in
         dune clean
         cd "$BENCH_DIR"
         [ -d java/target ] && rm -rf java/target
-        [ -d rust/target ] && rm -rf rust/target
-        [ -d rust-fast/target ] && rm -rf rust-fast/target
+        [ -d target ] && rm -rf target
         [ -d python/venv ] && rm -rf python/venv
         [ -d python-avro/venv ] && rm -rf python-avro/venv
         [ -d results ] && rm -rf results
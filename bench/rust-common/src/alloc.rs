@@ -0,0 +1,188 @@
+// Heap accounting for memory measurements
+// Each binary installs CountingAllocator as its global allocator. Counting
+// stays off until `enable` is called (by --measure-alloc or an operation that
+// always reports memory), so other runs only pay one relaxed load per
+// allocation.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicIsize, AtomicU64, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static CURRENT: AtomicIsize = AtomicIsize::new(0);
+static PEAK: AtomicIsize = AtomicIsize::new(0);
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// The system allocator, plus allocation counts and live and peak byte
+/// counts while enabled.
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    fn record_alloc(size: usize) {
+        if ENABLED.load(Ordering::Relaxed) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            ALLOCATED_BYTES.fetch_add(size as u64, Ordering::Relaxed);
+            Self::record(size as isize);
+        }
+    }
+
+    fn record(delta: isize) {
+        if ENABLED.load(Ordering::Relaxed) {
+            let current = CURRENT.fetch_add(delta, Ordering::Relaxed) + delta;
+            PEAK.fetch_max(current, Ordering::Relaxed);
+        }
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::record(-(layout.size() as isize));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // Counted as one allocation of the new size, like heap profilers do
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            Self::record_alloc(new_size);
+            Self::record(-(layout.size() as isize));
+        }
+        new_ptr
+    }
+}
+
+/// Start counting. Memory allocated earlier is not counted, but freeing it
+/// is, so only differences between two readings are meaningful.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Peak heap growth from the point it was created.
+pub struct PeakTracker {
+    baseline: isize,
+}
+
+impl PeakTracker {
+    pub fn start() -> PeakTracker {
+        let baseline = CURRENT.load(Ordering::Relaxed);
+        PEAK.store(baseline, Ordering::Relaxed);
+        PeakTracker { baseline }
+    }
+
+    /// Highest live heap size since `start`, above the size at `start`.
+    pub fn peak_bytes(&self) -> u64 {
+        (PEAK.load(Ordering::Relaxed) - self.baseline).max(0) as u64
+    }
+}
+
+/// Allocation figures for one operation, averaged per measured iteration
+/// except for the peaks.
+#[derive(Debug, Clone, Copy)]
+pub struct AllocStats {
+    pub allocations: u64,
+    pub allocated_bytes: u64,
+    /// Highest live heap growth over all measured iterations
+    pub peak_heap_bytes: u64,
+    /// Process-wide resident set high-water mark (VmHWM), Linux only
+    pub peak_rss_bytes: Option<u64>,
+}
+
+impl AllocStats {
+    pub fn allocations_per_record(&self, records: usize) -> f64 {
+        self.allocations as f64 / records.max(1) as f64
+    }
+
+    /// One-line text summary for `records` records per iteration.
+    pub fn summary(&self, records: usize) -> String {
+        let rss = match self.peak_rss_bytes {
+            Some(bytes) => format!("{} bytes", bytes),
+            None => "unavailable".to_string(),
+        };
+        format!(
+            "alloc: {} allocations ({:.2} per record), {} bytes allocated, peak heap {} bytes, peak RSS {}",
+            self.allocations,
+            self.allocations_per_record(records),
+            self.allocated_bytes,
+            self.peak_heap_bytes,
+            rss
+        )
+    }
+}
+
+/// Measures allocations between `start` and `finish`, leaving out any
+/// stretch between `pause` and `resume`. The peak is not paused: memory
+/// allocated while paused and still live counts towards it.
+pub struct AllocMeter {
+    allocations: u64,
+    allocated_bytes: u64,
+    mark: Option<(u64, u64)>,
+    peak: PeakTracker,
+}
+
+fn totals() -> (u64, u64) {
+    (
+        ALLOCATIONS.load(Ordering::Relaxed),
+        ALLOCATED_BYTES.load(Ordering::Relaxed),
+    )
+}
+
+impl AllocMeter {
+    pub fn start() -> AllocMeter {
+        enable();
+        AllocMeter {
+            allocations: 0,
+            allocated_bytes: 0,
+            mark: Some(totals()),
+            peak: PeakTracker::start(),
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if let Some((allocations, allocated_bytes)) = self.mark.take() {
+            let (now_allocations, now_bytes) = totals();
+            self.allocations += now_allocations - allocations;
+            self.allocated_bytes += now_bytes - allocated_bytes;
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.mark.is_none() {
+            self.mark = Some(totals());
+        }
+    }
+
+    pub fn finish(mut self, iterations: usize) -> AllocStats {
+        self.pause();
+        let iterations = iterations.max(1) as u64;
+        AllocStats {
+            allocations: self.allocations / iterations,
+            allocated_bytes: self.allocated_bytes / iterations,
+            peak_heap_bytes: self.peak.peak_bytes(),
+            peak_rss_bytes: peak_rss_bytes(),
+        }
+    }
+}
+
+/// VmHWM from /proc/self/status, or None where it is not available.
+pub fn peak_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
@@ -0,0 +1,48 @@
+// The seam between the shared workloads and a concrete Avro crate
+
+use crate::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::Path;
+
+/// Codec names accepted on the command line, in the order a codec sweep runs
+/// them. Names follow the Avro specification's `avro.codec` values.
+pub const CODEC_NAMES: [&str; 6] = ["null", "deflate", "snappy", "zstandard", "bzip2", "xz"];
+
+/// One Avro implementation under test, bound to a single schema.
+///
+/// Backends are built by their binary from a schema JSON string, so the same
+/// backend type can serve the Person workload and any other record type.
+pub trait AvroBackend {
+    /// `<language>-<library>` identifier reported in result records.
+    const IMPLEMENTATION: &'static str;
+
+    /// Backend-specific container codec selected by its Avro name.
+    type Codec: Copy;
+
+    /// Parse an `avro.codec` name. Unknown names, and codecs this build
+    /// cannot provide, are errors rather than a silent fallback to `null`.
+    fn codec(name: &str) -> Result<Self::Codec>;
+
+    /// Encode one value as a raw datum (no header or framing).
+    fn encode_datum<T: Serialize>(&mut self, value: &T) -> Result<Vec<u8>>;
+
+    /// Decode one raw datum written with this backend's schema.
+    fn decode_datum<T: DeserializeOwned>(&mut self, bytes: &[u8]) -> Result<T>;
+
+    /// Write `values` to an object container file at `path`. A block is
+    /// flushed once its uncompressed size reaches `block_size` bytes, or the
+    /// library's default when `None`.
+    fn write_container<T: Serialize>(
+        &mut self,
+        values: &[T],
+        codec: Self::Codec,
+        block_size: Option<usize>,
+        path: &Path,
+    ) -> Result<()>;
+
+    /// Read every record of the container file at `path`, deserializing each
+    /// into `T`, and return the number of records read. The first decode
+    /// error is returned, so a failing read cannot pass for a fast one.
+    fn read_container<T: DeserializeOwned>(&mut self, path: &Path) -> Result<usize>;
+}
@@ -0,0 +1,244 @@
+// Command line parsing shared by the benchmark binaries
+// Positional arguments keep the `[operation] [count] [compression]` order used
+// by run_comparison.sh; `--flag value` options may appear anywhere. Flags the
+// shared code does not know about are left for the binary to `take`.
+
+use crate::dataset::DatasetConfig;
+use crate::harness::HarnessConfig;
+use crate::report::Format;
+use crate::tree::TreeShape;
+use crate::workloads::Workload;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+// Valueless flags handled here for every binary
+const COMMON_SWITCHES: [&str; 1] = ["measure-alloc"];
+
+pub struct Options {
+    pub program: String,
+    /// Positional arguments after the program name; index 0 is the operation.
+    pub positional: Vec<String>,
+    pub operation: String,
+    pub count: i32,
+    pub compression: String,
+    pub harness: HarnessConfig,
+    pub format: Format,
+    pub workload: Workload,
+    pub tree: TreeShape,
+    /// JSON-lines Person file given with `--dataset`
+    pub dataset: Option<String>,
+    pub dataset_config: DatasetConfig,
+    /// Uncompressed container block size from `--block-size`; `None` keeps
+    /// each library's default
+    pub block_size: Option<usize>,
+    flags: BTreeMap<String, String>,
+}
+
+impl Options {
+    pub fn from_env(switches: &[&str]) -> Result<Options, String> {
+        Options::parse(std::env::args(), switches)
+    }
+
+    /// Parse `args` (including the program name). `switches` lists the flags
+    /// that take no value.
+    pub fn parse<I: IntoIterator<Item = String>>(
+        args: I,
+        switches: &[&str],
+    ) -> Result<Options, String> {
+        let mut args = args.into_iter();
+        let program = args.next().unwrap_or_default();
+        let mut positional = Vec::new();
+        let mut flags = BTreeMap::new();
+
+        while let Some(arg) = args.next() {
+            let Some(flag) = arg.strip_prefix("--") else {
+                positional.push(arg);
+                continue;
+            };
+            let (name, value) = match flag.split_once('=') {
+                Some((name, value)) => (name.to_string(), value.to_string()),
+                None if switches.contains(&flag) || COMMON_SWITCHES.contains(&flag) => {
+                    (flag.to_string(), "true".to_string())
+                }
+                None => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("Missing value for --{}", flag))?;
+                    (flag.to_string(), value)
+                }
+            };
+            flags.insert(name, value);
+        }
+
+        let mut options = Options {
+            program,
+            operation: positional
+                .first()
+                .cloned()
+                .unwrap_or_else(|| "encode".to_string()),
+            count: positional
+                .get(1)
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(10000),
+            compression: positional
+                .get(2)
+                .cloned()
+                .unwrap_or_else(|| "null".to_string()),
+            positional,
+            harness: HarnessConfig::default(),
+            format: Format::Text,
+            workload: Workload::Person,
+            tree: TreeShape::default(),
+            dataset: None,
+            dataset_config: DatasetConfig::default(),
+            block_size: None,
+            flags,
+        };
+
+        if let Some(warmup) = options.take_parsed("warmup")? {
+            options.harness.warmup = warmup;
+        }
+        if let Some(iterations) = options.take_parsed("iterations")? {
+            options.harness.iterations = iterations;
+        }
+        options.harness.measure_alloc = options.take_switch("measure-alloc");
+        if let Some(format) = options.take_parsed("format")? {
+            options.format = format;
+        }
+        if let Some(workload) = options.take_parsed("workload")? {
+            options.workload = workload;
+        }
+        if let Some(depth) = options.take_parsed("depth")? {
+            options.tree.depth = depth;
+        }
+        if let Some(fanout) = options.take_parsed("fanout")? {
+            options.tree.fanout = fanout;
+        }
+        if options.tree.depth == 0 || options.tree.fanout == 0 {
+            return Err("--depth and --fanout must be at least 1".to_string());
+        }
+        options.dataset = options.take("dataset");
+        if let Some(seed) = options.take_parsed("seed")? {
+            options.dataset_config.seed = seed;
+        }
+        if let Some(string_len) = options.take_parsed("string-len")? {
+            options.dataset_config.string_len = string_len;
+        }
+        if let Some(null_ratio) = options.take_parsed("null-ratio")? {
+            options.dataset_config.null_ratio = null_ratio;
+        }
+        if let Some(phones) = options.take_parsed("phones")? {
+            options.dataset_config.phones = phones;
+        }
+        if let Some(items) = options.take_parsed("items")? {
+            options.dataset_config.items = items;
+        }
+        if !(0.0..=1.0).contains(&options.dataset_config.null_ratio) {
+            return Err("--null-ratio must be between 0 and 1".to_string());
+        }
+        if let Some(block_size) = options.take("block-size") {
+            options.block_size = Some(parse_size(&block_size)?);
+        }
+
+        Ok(options)
+    }
+
+    /// Remove and return the value of `--flag`.
+    pub fn take(&mut self, flag: &str) -> Option<String> {
+        self.flags.remove(flag)
+    }
+
+    pub fn take_parsed<T: FromStr>(&mut self, flag: &str) -> Result<Option<T>, String> {
+        match self.take(flag) {
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("Invalid value for --{}: {}", flag, value)),
+            None => Ok(None),
+        }
+    }
+
+    /// Remove a valueless `--flag`, returning whether it was given.
+    pub fn take_switch(&mut self, flag: &str) -> bool {
+        self.take(flag).is_some()
+    }
+
+    /// Fail on any flag that no one has taken.
+    pub fn finish(&self) -> Result<(), String> {
+        match self.flags.keys().next() {
+            Some(flag) => Err(format!("Unknown option --{}", flag)),
+            None => Ok(()),
+        }
+    }
+
+    /// Positional argument `index` (0 is the operation), or an error naming
+    /// what was expected there.
+    pub fn required(&self, index: usize, what: &str) -> Result<&str, String> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| format!("{} requires {}", self.operation, what))
+    }
+
+    /// The output path of an `<operation> [count] <output>` command line:
+    /// positional 2 when positional 1 is the count, positional 1 otherwise.
+    pub fn output_path(&self) -> Result<&str, String> {
+        let counted = self
+            .positional
+            .get(1)
+            .is_some_and(|s| s.parse::<i32>().is_ok());
+        self.required(if counted { 2 } else { 1 }, "an output path")
+    }
+}
+
+/// Print `message` and the usage line, then exit with status 1.
+pub fn usage_error(program: &str, usage: &str, message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!("Usage: {} {}", program, usage);
+    std::process::exit(1);
+}
+
+/// Exit with status 1 if an operation failed.
+pub fn exit_on_error(result: crate::Result<()>) {
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+/// A byte count written as `N`, `NK` or `NM` (KiB and MiB), at least 1.
+pub fn parse_size(s: &str) -> Result<usize, String> {
+    let invalid = || format!("Invalid size '{}' (expected N, NK or NM bytes)", s);
+    let (digits, unit) = match s.to_ascii_uppercase().strip_suffix(['K', 'M']) {
+        Some(digits) if s.ends_with(['k', 'K']) => (digits.to_string(), 1024),
+        Some(digits) => (digits.to_string(), 1024 * 1024),
+        None => (s.to_string(), 1),
+    };
+    match digits.trim().parse::<usize>() {
+        Ok(n) if n > 0 => n.checked_mul(unit).ok_or_else(invalid),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_size_accepts_bytes_kib_and_mib() {
+        assert_eq!(parse_size("1"), Ok(1));
+        assert_eq!(parse_size("4000"), Ok(4000));
+        assert_eq!(parse_size("64k"), Ok(64 * 1024));
+        assert_eq!(parse_size("64K"), Ok(64 * 1024));
+        assert_eq!(parse_size("2m"), Ok(2 * 1024 * 1024));
+        assert_eq!(parse_size("2M"), Ok(2 * 1024 * 1024));
+    }
+
+    #[test]
+    fn parse_size_rejects_zero_and_garbage() {
+        for size in ["", "0", "0K", "K", "-1", "1.5M", "12G", "abc", "1KB"] {
+            assert!(parse_size(size).is_err(), "{:?} was accepted", size);
+        }
+        assert!(parse_size(&format!("{}M", usize::MAX)).is_err());
+    }
+}
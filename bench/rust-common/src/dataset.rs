@@ -0,0 +1,116 @@
+// Shared Person dataset files
+// A dataset is JSON lines, one Person object per line with the Avro field
+// names ({"name": ..., "age": ..., "email": null, "phone_numbers": [...]}), so
+// every language can load the same records instead of re-implementing
+// create_person. `generate-dataset` writes one from a seeded generator.
+
+use crate::cli::Options;
+use crate::person::{create_people, Person};
+use crate::rng::{LenRange, Rng};
+use crate::Result;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+
+/// Generator settings, set with `--seed`, `--string-len`, `--null-ratio`,
+/// `--phones` and `--items`. The `--schema` generator in avro-rust-bench uses
+/// them too, applying `null_ratio` to unions with a null branch.
+#[derive(Debug, Clone, Copy)]
+pub struct DatasetConfig {
+    pub seed: u64,
+    /// Length of `name` and of the local part of `email`
+    pub string_len: LenRange,
+    /// Share of records whose `email` is null
+    pub null_ratio: f64,
+    /// Number of `phone_numbers`
+    pub phones: LenRange,
+    /// Entries in generated arrays and maps (`--schema` only)
+    pub items: LenRange,
+}
+
+impl Default for DatasetConfig {
+    // Roughly the shape of create_person: short strings, two thirds of the
+    // emails null, one to three phone numbers
+    fn default() -> Self {
+        DatasetConfig {
+            seed: 42,
+            string_len: LenRange::new(8, 16),
+            null_ratio: 2.0 / 3.0,
+            phones: LenRange::new(1, 3),
+            items: LenRange::new(0, 4),
+        }
+    }
+}
+
+pub fn generate_person(rng: &mut Rng, config: &DatasetConfig) -> Person {
+    Person {
+        name: rng.string(config.string_len),
+        age: 18 + (rng.next_u64() % 73) as i32,
+        email: if rng.chance(config.null_ratio) {
+            None
+        } else {
+            Some(format!("{}@example.com", rng.string(config.string_len)))
+        },
+        phone_numbers: (0..rng.range(config.phones))
+            .map(|_| format!("+1-555-{:04}", rng.next_u64() % 10_000))
+            .collect(),
+    }
+}
+
+pub fn generate_people(count: i32, config: &DatasetConfig) -> Vec<Person> {
+    let mut rng = Rng::new(config.seed);
+    (0..count)
+        .map(|_| generate_person(&mut rng, config))
+        .collect()
+}
+
+pub fn write_dataset(path: &str, people: &[Person]) -> Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    for person in people {
+        serde_json::to_writer(&mut out, person)?;
+        out.write_all(b"\n")?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+pub fn read_dataset(path: &str) -> Result<Vec<Person>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut people = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let person =
+            serde_json::from_str(&line).map_err(|err| format!("{}:{}: {}", path, i + 1, err))?;
+        people.push(person);
+    }
+    Ok(people)
+}
+
+/// The Person records to benchmark: every record in `--dataset` when given,
+/// otherwise `count` records from create_person.
+pub fn people(options: &Options) -> Result<Vec<Person>> {
+    match &options.dataset {
+        Some(path) => read_dataset(path),
+        None => Ok(create_people(options.count)),
+    }
+}
+
+/// `generate-dataset [count] <output>`
+pub fn generate(options: &Options) -> Result<()> {
+    let path = options.output_path()?;
+    let config = &options.dataset_config;
+    let people = generate_people(options.count, config);
+    write_dataset(path, &people)?;
+    println!(
+        "Wrote {} records to {} (seed {}, string length {}, null ratio {}, phones {})",
+        people.len(),
+        path,
+        config.seed,
+        config.string_len,
+        config.null_ratio,
+        config.phones
+    );
+    Ok(())
+}
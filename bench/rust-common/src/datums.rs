@@ -0,0 +1,197 @@
+// Golden datum files for byte-exact comparison between implementations
+// A datum file holds one lowercase hex-encoded datum per line, in the order
+// of the input records: create_person(i) on line i, or the i-th record of the
+// --dataset file. Plain text keeps it easy to produce from OCaml, Java or
+// Python and to inspect with diff.
+
+use crate::backend::AvroBackend;
+use crate::Result;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::ops::Range;
+
+/// Encode every value with `backend` and write them to `path`.
+pub fn emit<B: AvroBackend, T: Serialize>(backend: &mut B, values: &[T], path: &str) -> Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    let mut total_bytes = 0;
+    for value in values {
+        let bytes = backend.encode_datum(value)?;
+        total_bytes += bytes.len();
+        writeln!(out, "{}", to_hex(&bytes))?;
+    }
+    out.flush()?;
+
+    println!(
+        "Wrote {} datums ({} bytes) from {} to {}",
+        values.len(),
+        total_bytes,
+        B::IMPLEMENTATION,
+        path
+    );
+    Ok(())
+}
+
+/// Diff two datum files, reporting the first differing record, Person field
+/// and byte offset. Returns an error when the files differ.
+pub fn compare(path_a: &str, path_b: &str) -> Result<()> {
+    let a = read_datums(path_a)?;
+    let b = read_datums(path_b)?;
+
+    for (i, (datum_a, datum_b)) in a.iter().zip(&b).enumerate() {
+        if datum_a == datum_b {
+            continue;
+        }
+        // A datum that is a prefix of the other differs at its end
+        let offset = datum_a
+            .iter()
+            .zip(datum_b)
+            .position(|(x, y)| x != y)
+            .unwrap_or(datum_a.len().min(datum_b.len()));
+        let field = field_at(datum_a, offset)
+            .or_else(|| field_at(datum_b, offset))
+            .unwrap_or("<unknown>");
+
+        println!(
+            "First difference at record {}, field {}, byte offset {}",
+            i, field, offset
+        );
+        println!(
+            "  {}: {} ({} bytes)",
+            path_a,
+            to_hex(datum_a),
+            datum_a.len()
+        );
+        println!(
+            "  {}: {} ({} bytes)",
+            path_b,
+            to_hex(datum_b),
+            datum_b.len()
+        );
+        return Err(format!("{} and {} differ at record {}", path_a, path_b, i).into());
+    }
+
+    if a.len() != b.len() {
+        return Err(format!(
+            "{} has {} datums but {} has {}; the first {} are identical",
+            path_a,
+            a.len(),
+            path_b,
+            b.len(),
+            a.len().min(b.len())
+        )
+        .into());
+    }
+
+    println!("{} datums identical", a.len());
+    Ok(())
+}
+
+fn read_datums(path: &str) -> Result<Vec<Vec<u8>>> {
+    let file = BufReader::new(File::open(path)?);
+    let mut datums = Vec::new();
+    for (line_number, line) in file.lines().enumerate() {
+        let datum = from_hex(line?.trim())
+            .ok_or_else(|| format!("{}:{}: invalid hex datum", path, line_number + 1))?;
+        datums.push(datum);
+    }
+    Ok(datums)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+// Name of the Person field whose encoding covers `offset`. An offset just past
+// the end of the datum is attributed to the last field.
+fn field_at(datum: &[u8], offset: usize) -> Option<&'static str> {
+    let spans = person_field_spans(datum)?;
+    spans
+        .iter()
+        .find(|(_, span)| span.contains(&offset))
+        .or_else(|| spans.last().filter(|(_, span)| span.end == offset))
+        .map(|(name, _)| *name)
+}
+
+// Byte ranges of each Person field in a binary-encoded datum, or None if the
+// bytes do not parse as a Person
+fn person_field_spans(datum: &[u8]) -> Option<Vec<(&'static str, Range<usize>)>> {
+    let mut cursor = Cursor { datum, pos: 0 };
+    let mut spans = Vec::new();
+
+    let start = cursor.pos;
+    cursor.skip_string()?;
+    spans.push(("name", start..cursor.pos));
+
+    let start = cursor.pos;
+    cursor.read_long()?;
+    spans.push(("age", start..cursor.pos));
+
+    let start = cursor.pos;
+    match cursor.read_long()? {
+        0 => {}
+        1 => cursor.skip_string()?,
+        _ => return None,
+    }
+    spans.push(("email", start..cursor.pos));
+
+    let start = cursor.pos;
+    loop {
+        let mut items = cursor.read_long()?;
+        if items == 0 {
+            break;
+        }
+        if items < 0 {
+            // Negative counts are followed by the block size in bytes
+            items = -items;
+            cursor.read_long()?;
+        }
+        for _ in 0..items {
+            cursor.skip_string()?;
+        }
+    }
+    spans.push(("phone_numbers", start..cursor.pos));
+
+    (cursor.pos == datum.len()).then_some(spans)
+}
+
+struct Cursor<'a> {
+    datum: &'a [u8],
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    // Zig-zag variable-length long
+    fn read_long(&mut self) -> Option<i64> {
+        let mut value: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = *self.datum.get(self.pos)?;
+            self.pos += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some((value >> 1) as i64 ^ -((value & 1) as i64));
+            }
+        }
+        None
+    }
+
+    fn skip_string(&mut self) -> Option<()> {
+        let len = usize::try_from(self.read_long()?).ok()?;
+        let end = self.pos.checked_add(len)?;
+        if end > self.datum.len() {
+            return None;
+        }
+        self.pos = end;
+        Some(())
+    }
+}
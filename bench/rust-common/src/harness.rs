@@ -0,0 +1,141 @@
+// In-process benchmark harness
+// Runs warmup and measured iterations inside the process so that timings are
+// not dominated by process startup, and summarises the measured samples.
+
+use crate::alloc::{AllocMeter, AllocStats};
+use crate::Result;
+use std::hint::black_box;
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy)]
+pub struct HarnessConfig {
+    pub warmup: usize,
+    pub iterations: usize,
+    /// Count allocations during the measured iterations
+    pub measure_alloc: bool,
+}
+
+impl Default for HarnessConfig {
+    // A single measured pass matches the original one-shot behaviour
+    fn default() -> Self {
+        HarnessConfig {
+            warmup: 0,
+            iterations: 1,
+            measure_alloc: false,
+        }
+    }
+}
+
+/// Summary of the measured iterations, all times in seconds.
+#[derive(Debug, Clone)]
+pub struct Stats {
+    pub warmup: usize,
+    pub iterations: usize,
+    pub mean: f64,
+    pub stddev: f64,
+    pub min: f64,
+    pub max: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    /// Present when the harness was asked to measure allocations
+    pub alloc: Option<AllocStats>,
+}
+
+impl Stats {
+    pub fn from_samples(warmup: usize, samples: &[f64]) -> Stats {
+        assert!(!samples.is_empty(), "at least one measured iteration");
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+
+        let n = sorted.len() as f64;
+        let mean = sorted.iter().sum::<f64>() / n;
+        // Sample standard deviation; zero for a single iteration
+        let stddev = if sorted.len() > 1 {
+            let variance = sorted.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            variance.sqrt()
+        } else {
+            0.0
+        };
+
+        Stats {
+            warmup,
+            iterations: sorted.len(),
+            mean,
+            stddev,
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            p50: percentile(&sorted, 50.0),
+            p95: percentile(&sorted, 95.0),
+            p99: percentile(&sorted, 99.0),
+            alloc: None,
+        }
+    }
+
+    /// One-line text summary of the measured iterations.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} iterations ({} warmup): mean {:.6}s ± {:.6}s, min {:.6}s, max {:.6}s, p50 {:.6}s, p95 {:.6}s, p99 {:.6}s",
+            self.iterations,
+            self.warmup,
+            self.mean,
+            self.stddev,
+            self.min,
+            self.max,
+            self.p50,
+            self.p95,
+            self.p99
+        )
+    }
+}
+
+// Nearest-rank percentile over already sorted samples
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Run `f` for the configured warmup iterations, then time each measured
+/// iteration. The closure's result is kept alive until after the clock stops
+/// so the work cannot be optimised away and drop cost is not measured. The
+/// first error from any iteration is returned.
+pub fn measure<T, F: FnMut() -> Result<T>>(config: &HarnessConfig, mut f: F) -> Result<Stats> {
+    measure_with_setup(config, || Ok(()), |()| f())
+}
+
+/// Like [`measure`], but each iteration first runs `setup`, untimed, and
+/// hands its output to `f`. For operations that consume their input.
+/// Allocations made by `setup` are not counted.
+pub fn measure_with_setup<S, T, G, F>(
+    config: &HarnessConfig,
+    mut setup: G,
+    mut f: F,
+) -> Result<Stats>
+where
+    G: FnMut() -> Result<S>,
+    F: FnMut(S) -> Result<T>,
+{
+    for _ in 0..config.warmup {
+        black_box(f(setup()?)?);
+    }
+
+    let mut samples = Vec::with_capacity(config.iterations);
+    let mut meter = config.measure_alloc.then(AllocMeter::start);
+    for _ in 0..config.iterations.max(1) {
+        if let Some(meter) = &mut meter {
+            meter.pause();
+        }
+        let input = setup()?;
+        if let Some(meter) = &mut meter {
+            meter.resume();
+        }
+        let start = Instant::now();
+        let result = f(input)?;
+        samples.push(start.elapsed().as_secs_f64());
+        black_box(result);
+    }
+
+    let mut stats = Stats::from_samples(config.warmup, &samples);
+    stats.alloc = meter.map(|meter| meter.finish(samples.len()));
+    Ok(stats)
+}
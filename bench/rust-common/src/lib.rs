@@ -0,0 +1,28 @@
+//! Shared pieces of the Rust Avro benchmarks
+//!
+//! The Person, logical and tree workloads, data generation, in-process harness, result output and
+//! argument parsing live here. Each benchmark binary implements
+//! [`AvroBackend`] for one Avro crate and hands it to the generic workloads in
+//! [`workloads`].
+
+pub mod alloc;
+pub mod backend;
+pub mod cli;
+pub mod dataset;
+pub mod datums;
+pub mod fingerprint;
+pub mod harness;
+pub mod logical;
+pub mod person;
+pub mod report;
+pub mod rng;
+pub mod tree;
+pub mod workloads;
+
+pub use backend::AvroBackend;
+pub use logical::{create_logical_record, LogicalRecord, LOGICAL_SCHEMA};
+pub use person::{create_person, Person, PERSON_SCHEMA};
+pub use tree::{Node, TREE_SCHEMA};
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+pub type Result<T> = std::result::Result<T, Error>;
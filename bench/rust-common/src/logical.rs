@@ -0,0 +1,206 @@
+// Logical types workload
+// One record covering every logical type in the OCaml Logical module: date,
+// time and timestamp variants (including local timestamps), decimal on bytes
+// and on fixed, uuid and duration. Fields use the usual Rust types (chrono,
+// uuid, rust_decimal).
+//
+// The serde shape is the one serde_avro_fast maps onto the logical types:
+// dates and times as their underlying int/long, uuid as a string, decimals as
+// strings and duration as a months/days/milliseconds struct. apache-avro 0.20
+// cannot carry decimal or duration through serde, so avro-rust-bench converts
+// to and from its Value instead, using the conversions below.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub const LOGICAL_SCHEMA: &str = r#"
+{
+    "type": "record",
+    "name": "LogicalRecord",
+    "fields": [
+        {"name": "date", "type": {"type": "int", "logicalType": "date"}},
+        {"name": "time_millis", "type": {"type": "int", "logicalType": "time-millis"}},
+        {"name": "time_micros", "type": {"type": "long", "logicalType": "time-micros"}},
+        {"name": "timestamp_millis", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+        {"name": "timestamp_micros", "type": {"type": "long", "logicalType": "timestamp-micros"}},
+        {"name": "local_timestamp_millis", "type": {"type": "long", "logicalType": "local-timestamp-millis"}},
+        {"name": "local_timestamp_micros", "type": {"type": "long", "logicalType": "local-timestamp-micros"}},
+        {"name": "price", "type": {"type": "bytes", "logicalType": "decimal", "precision": 12, "scale": 2}},
+        {"name": "balance", "type": {"type": "fixed", "name": "Balance", "size": 8, "logicalType": "decimal", "precision": 18, "scale": 4}},
+        {"name": "id", "type": {"type": "string", "logicalType": "uuid"}},
+        {"name": "interval", "type": {"type": "fixed", "name": "Interval", "size": 12, "logicalType": "duration"}}
+    ]
+}
+"#;
+
+/// Scale of the `price` decimal (bytes).
+pub const PRICE_SCALE: u32 = 2;
+/// Scale and size of the `balance` decimal (fixed).
+pub const BALANCE_SCALE: u32 = 4;
+pub const BALANCE_SIZE: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogicalRecord {
+    #[serde(with = "date_serde")]
+    pub date: NaiveDate,
+    #[serde(with = "time_millis_serde")]
+    pub time_millis: NaiveTime,
+    #[serde(with = "time_micros_serde")]
+    pub time_micros: NaiveTime,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub timestamp_millis: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_microseconds")]
+    pub timestamp_micros: DateTime<Utc>,
+    #[serde(with = "local_timestamp_millis_serde")]
+    pub local_timestamp_millis: NaiveDateTime,
+    #[serde(with = "local_timestamp_micros_serde")]
+    pub local_timestamp_micros: NaiveDateTime,
+    pub price: Decimal,
+    pub balance: Decimal,
+    pub id: Uuid,
+    pub interval: Duration,
+}
+
+/// Avro duration: three independent unsigned counts, not a single span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Duration {
+    pub months: u32,
+    pub days: u32,
+    pub milliseconds: u32,
+}
+
+pub fn create_logical_record(i: i32) -> LogicalRecord {
+    let n = i64::from(i);
+    let timestamp_millis = 1_600_000_000_000 + n * 1_001;
+    let timestamp_micros = 1_600_000_000_000_000 + n * 1_000_003;
+    LogicalRecord {
+        date: days_to_date(18_262 + i % 3_650).unwrap(),
+        time_millis: millis_to_time(((n * 7_919_011) % 86_400_000) as i32).unwrap(),
+        time_micros: micros_to_time((n * 7_919_000_017) % 86_400_000_000).unwrap(),
+        timestamp_millis: DateTime::from_timestamp_millis(timestamp_millis).unwrap(),
+        timestamp_micros: DateTime::from_timestamp_micros(timestamp_micros).unwrap(),
+        local_timestamp_millis: millis_to_local(timestamp_millis).unwrap(),
+        local_timestamp_micros: micros_to_local(timestamp_micros).unwrap(),
+        price: Decimal::new((n * 12_345) % 10_000_000 - 5_000_000, PRICE_SCALE),
+        balance: Decimal::new(n * 1_000_003 - 500_000_000, BALANCE_SCALE),
+        id: Uuid::from_u128(
+            0x9e37_79b9_7f4a_7c15_f39c_c060_5ced_c835_u128.wrapping_mul(n as u128 + 1),
+        ),
+        interval: Duration {
+            months: (i % 24) as u32,
+            days: (i % 31) as u32,
+            milliseconds: ((n * 1_000) % 86_400_000) as u32,
+        },
+    }
+}
+
+pub fn create_logical_records(count: i32) -> Vec<LogicalRecord> {
+    (0..count).map(create_logical_record).collect()
+}
+
+const UNIX_EPOCH_DATE: NaiveDate = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+
+pub fn date_to_days(date: NaiveDate) -> i32 {
+    (date - UNIX_EPOCH_DATE).num_days() as i32
+}
+
+pub fn days_to_date(days: i32) -> Option<NaiveDate> {
+    UNIX_EPOCH_DATE.checked_add_signed(chrono::TimeDelta::days(days.into()))
+}
+
+pub fn time_to_millis(time: NaiveTime) -> i32 {
+    (time.num_seconds_from_midnight() * 1_000 + time.nanosecond() / 1_000_000) as i32
+}
+
+pub fn millis_to_time(millis: i32) -> Option<NaiveTime> {
+    let millis = u32::try_from(millis).ok()?;
+    NaiveTime::from_num_seconds_from_midnight_opt(millis / 1_000, millis % 1_000 * 1_000_000)
+}
+
+pub fn time_to_micros(time: NaiveTime) -> i64 {
+    i64::from(time.num_seconds_from_midnight()) * 1_000_000 + i64::from(time.nanosecond() / 1_000)
+}
+
+pub fn micros_to_time(micros: i64) -> Option<NaiveTime> {
+    let micros = u64::try_from(micros).ok()?;
+    NaiveTime::from_num_seconds_from_midnight_opt(
+        u32::try_from(micros / 1_000_000).ok()?,
+        (micros % 1_000_000 * 1_000) as u32,
+    )
+}
+
+pub fn millis_to_local(millis: i64) -> Option<NaiveDateTime> {
+    DateTime::from_timestamp_millis(millis).map(|dt| dt.naive_utc())
+}
+
+pub fn micros_to_local(micros: i64) -> Option<NaiveDateTime> {
+    DateTime::from_timestamp_micros(micros).map(|dt| dt.naive_utc())
+}
+
+// serde `with` modules mapping each chrono type onto its Avro base type
+
+macro_rules! logical_serde {
+    ($module:ident, $ty:ty, $base:ty, $to:expr, $from:expr, $what:literal) => {
+        mod $module {
+            use super::*;
+            use serde::{Deserializer, Serializer};
+
+            pub fn serialize<S: Serializer>(value: &$ty, serializer: S) -> Result<S::Ok, S::Error> {
+                let base: $base = $to(*value);
+                base.serialize(serializer)
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<$ty, D::Error> {
+                let base = <$base>::deserialize(deserializer)?;
+                $from(base).ok_or_else(|| {
+                    serde::de::Error::custom(format!("{} out of range for {}", base, $what))
+                })
+            }
+        }
+    };
+}
+
+logical_serde!(
+    date_serde,
+    NaiveDate,
+    i32,
+    date_to_days,
+    days_to_date,
+    "date"
+);
+logical_serde!(
+    time_millis_serde,
+    NaiveTime,
+    i32,
+    time_to_millis,
+    millis_to_time,
+    "time-millis"
+);
+logical_serde!(
+    time_micros_serde,
+    NaiveTime,
+    i64,
+    time_to_micros,
+    micros_to_time,
+    "time-micros"
+);
+logical_serde!(
+    local_timestamp_millis_serde,
+    NaiveDateTime,
+    i64,
+    |local: NaiveDateTime| local.and_utc().timestamp_millis(),
+    millis_to_local,
+    "local-timestamp-millis"
+);
+logical_serde!(
+    local_timestamp_micros_serde,
+    NaiveDateTime,
+    i64,
+    |local: NaiveDateTime| local.and_utc().timestamp_micros(),
+    micros_to_local,
+    "local-timestamp-micros"
+);
@@ -0,0 +1,43 @@
+// The Person record shared by every language's benchmark
+
+use serde::{Deserialize, Serialize};
+
+pub const PERSON_SCHEMA: &str = r#"
+{
+    "type": "record",
+    "name": "Person",
+    "fields": [
+        {"name": "name", "type": "string"},
+        {"name": "age", "type": "int"},
+        {"name": "email", "type": ["null", "string"]},
+        {"name": "phone_numbers", "type": {"type": "array", "items": "string"}}
+    ]
+}
+"#;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Person {
+    pub name: String,
+    pub age: i32,
+    pub email: Option<String>,
+    pub phone_numbers: Vec<String>,
+}
+
+pub fn create_person(i: i32) -> Person {
+    Person {
+        name: format!("Person_{}", i),
+        age: 20 + (i % 60),
+        email: if i % 3 == 0 {
+            Some(format!("person{}@example.com", i))
+        } else {
+            None
+        },
+        phone_numbers: (0..(1 + i % 3))
+            .map(|j| format!("+1-555-{:04}", i * 10 + j))
+            .collect(),
+    }
+}
+
+pub fn create_people(count: i32) -> Vec<Person> {
+    (0..count).map(create_person).collect()
+}
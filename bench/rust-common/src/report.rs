@@ -0,0 +1,246 @@
+// Machine-readable benchmark results
+// Every measurement becomes one flat ResultRecord so dashboards can ingest
+// JSON lines or CSV instead of scraping the text output. The field layout is
+// documented in bench/README.md so other language benches can emit the same one.
+
+use crate::harness::Stats;
+use crate::workloads::Workload;
+use serde::Serialize;
+use std::cell::Cell;
+use std::str::FromStr;
+
+/// Bumped whenever a field is renamed, removed or changes meaning.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!(
+                "Unknown output format '{}' (expected text, json or csv)",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResultRecord {
+    pub format_version: u32,
+    pub implementation: &'static str,
+    pub operation: String,
+    pub workload: &'static str,
+    pub count: u64,
+    pub codec: Option<String>,
+    pub elapsed_secs: f64,
+    pub bytes: u64,
+    pub mb_per_sec: f64,
+    pub records_per_sec: f64,
+    pub warmup: Option<usize>,
+    pub iterations: Option<usize>,
+    pub mean_secs: Option<f64>,
+    pub stddev_secs: Option<f64>,
+    pub min_secs: Option<f64>,
+    pub max_secs: Option<f64>,
+    pub p50_secs: Option<f64>,
+    pub p95_secs: Option<f64>,
+    pub p99_secs: Option<f64>,
+    pub allocations: Option<u64>,
+    pub allocated_bytes: Option<u64>,
+    pub allocations_per_record: Option<f64>,
+    pub peak_heap_bytes: Option<u64>,
+    pub peak_rss_bytes: Option<u64>,
+    pub block_size: Option<u64>,
+    pub cache_hit_rate: Option<f64>,
+}
+
+const CSV_HEADER: &str = "format_version,implementation,operation,workload,count,codec,elapsed_secs,bytes,mb_per_sec,records_per_sec,warmup,iterations,mean_secs,stddev_secs,min_secs,max_secs,p50_secs,p95_secs,p99_secs,allocations,allocated_bytes,allocations_per_record,peak_heap_bytes,peak_rss_bytes,block_size,cache_hit_rate";
+
+impl ResultRecord {
+    /// Record for a single timed pass.
+    pub fn new(
+        implementation: &'static str,
+        operation: &str,
+        count: usize,
+        bytes: u64,
+        elapsed_secs: f64,
+    ) -> ResultRecord {
+        ResultRecord {
+            format_version: FORMAT_VERSION,
+            implementation,
+            operation: operation.to_string(),
+            workload: Workload::Person.name(),
+            count: count as u64,
+            codec: None,
+            elapsed_secs,
+            bytes,
+            mb_per_sec: (bytes as f64 / elapsed_secs) / 1_000_000.0,
+            records_per_sec: count as f64 / elapsed_secs,
+            warmup: None,
+            iterations: None,
+            mean_secs: None,
+            stddev_secs: None,
+            min_secs: None,
+            max_secs: None,
+            p50_secs: None,
+            p95_secs: None,
+            p99_secs: None,
+            allocations: None,
+            allocated_bytes: None,
+            allocations_per_record: None,
+            peak_heap_bytes: None,
+            peak_rss_bytes: None,
+            block_size: None,
+            cache_hit_rate: None,
+        }
+    }
+
+    /// Record for a harness measurement. `elapsed_secs` is the mean; the
+    /// iteration statistics are only filled in when more than one iteration
+    /// was measured, the allocation fields when allocations were counted.
+    pub fn measured(
+        implementation: &'static str,
+        operation: &str,
+        count: usize,
+        bytes: u64,
+        stats: &Stats,
+    ) -> ResultRecord {
+        let mut record = ResultRecord::new(implementation, operation, count, bytes, stats.mean);
+        if stats.iterations > 1 {
+            record.warmup = Some(stats.warmup);
+            record.iterations = Some(stats.iterations);
+            record.mean_secs = Some(stats.mean);
+            record.stddev_secs = Some(stats.stddev);
+            record.min_secs = Some(stats.min);
+            record.max_secs = Some(stats.max);
+            record.p50_secs = Some(stats.p50);
+            record.p95_secs = Some(stats.p95);
+            record.p99_secs = Some(stats.p99);
+        }
+        if let Some(alloc) = &stats.alloc {
+            record.allocations = Some(alloc.allocations);
+            record.allocated_bytes = Some(alloc.allocated_bytes);
+            record.allocations_per_record = Some(alloc.allocations_per_record(count));
+            record.peak_heap_bytes = Some(alloc.peak_heap_bytes);
+            record.peak_rss_bytes = alloc.peak_rss_bytes;
+        }
+        record
+    }
+
+    pub fn with_codec(mut self, codec: &str) -> ResultRecord {
+        self.codec = Some(codec.to_string());
+        self
+    }
+
+    /// Set the container block size when one was chosen.
+    pub fn with_block_size(mut self, block_size: Option<usize>) -> ResultRecord {
+        self.block_size = block_size.map(|size| size as u64);
+        self
+    }
+
+    /// Set the share of schema lookups served from a client cache.
+    pub fn with_cache_hit_rate(mut self, hit_rate: Option<f64>) -> ResultRecord {
+        self.cache_hit_rate = hit_rate;
+        self
+    }
+
+    pub fn with_workload(mut self, workload: &'static str) -> ResultRecord {
+        self.workload = workload;
+        self
+    }
+
+    fn to_csv(&self) -> String {
+        fn opt<T: ToString>(value: Option<T>) -> String {
+            value.map(|v| v.to_string()).unwrap_or_default()
+        }
+        [
+            self.format_version.to_string(),
+            csv_field(self.implementation),
+            csv_field(&self.operation),
+            csv_field(self.workload),
+            self.count.to_string(),
+            csv_field(self.codec.as_deref().unwrap_or("")),
+            self.elapsed_secs.to_string(),
+            self.bytes.to_string(),
+            self.mb_per_sec.to_string(),
+            self.records_per_sec.to_string(),
+            opt(self.warmup),
+            opt(self.iterations),
+            opt(self.mean_secs),
+            opt(self.stddev_secs),
+            opt(self.min_secs),
+            opt(self.max_secs),
+            opt(self.p50_secs),
+            opt(self.p95_secs),
+            opt(self.p99_secs),
+            opt(self.allocations),
+            opt(self.allocated_bytes),
+            opt(self.allocations_per_record),
+            opt(self.peak_heap_bytes),
+            opt(self.peak_rss_bytes),
+            opt(self.block_size),
+            opt(self.cache_hit_rate),
+        ]
+        .join(",")
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Writes results to stdout in the selected format. Text keeps the
+/// human-readable sentences; JSON emits one object per line; CSV emits a
+/// single header followed by one row per record.
+pub struct Reporter {
+    format: Format,
+    csv_header_written: Cell<bool>,
+}
+
+impl Reporter {
+    pub fn new(format: Format) -> Reporter {
+        Reporter {
+            format,
+            csv_header_written: Cell::new(false),
+        }
+    }
+
+    pub fn is_text(&self) -> bool {
+        self.format == Format::Text
+    }
+
+    /// Emit `records`, or run `text` to print the human-readable form.
+    pub fn emit<F: FnOnce()>(&self, records: &[ResultRecord], text: F) {
+        match self.format {
+            Format::Text => text(),
+            Format::Json => {
+                for record in records {
+                    println!("{}", serde_json::to_string(record).unwrap());
+                }
+            }
+            Format::Csv => {
+                if !self.csv_header_written.replace(true) {
+                    println!("{}", CSV_HEADER);
+                }
+                for record in records {
+                    println!("{}", record.to_csv());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+// Seeded pseudo-random numbers for generated data
+// SplitMix64: tiny, fast and fully specified, so another language can
+// reproduce a stream from the same seed without pulling in a crate.
+
+/// A SplitMix64 generator. The same seed always yields the same sequence.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in `range` (inclusive). Modulo bias is negligible for the
+    /// small ranges used here.
+    pub fn range(&mut self, range: LenRange) -> usize {
+        let span = (range.max - range.min) as u64 + 1;
+        range.min + (self.next_u64() % span) as usize
+    }
+
+    /// True with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        self.next_f64() < p
+    }
+
+    /// Lowercase alphanumeric string with a length drawn from `len`.
+    pub fn string(&mut self, len: LenRange) -> String {
+        const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+        let len = self.range(len);
+        (0..len)
+            .map(|_| ALPHABET[(self.next_u64() % ALPHABET.len() as u64) as usize] as char)
+            .collect()
+    }
+}
+
+/// An inclusive length range, written `MIN..MAX` or a single `N` on the
+/// command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LenRange {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl LenRange {
+    pub const fn new(min: usize, max: usize) -> LenRange {
+        LenRange { min, max }
+    }
+}
+
+impl std::str::FromStr for LenRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("Invalid range '{}' (expected N or MIN..MAX)", s);
+        let (min, max) = match s.split_once("..") {
+            Some((min, max)) => (min, max),
+            None => (s, s),
+        };
+        let min = min.trim().parse().map_err(|_| invalid())?;
+        let max = max.trim().parse().map_err(|_| invalid())?;
+        if min > max {
+            return Err(invalid());
+        }
+        Ok(LenRange { min, max })
+    }
+}
+
+impl std::fmt::Display for LenRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.min, self.max)
+    }
+}
@@ -0,0 +1,222 @@
+// Recursive schema workload
+// A self-referencing Node record, mirroring test_recursive.ml on the OCaml
+// side. Fan-out 1 gives a linked list, fan-out 2 a binary tree. Building,
+// comparing and dropping trees is iterative here, so only the Avro library
+// recurses; depth_stress uses that to find out how each library handles deep
+// nesting.
+
+use crate::backend::AvroBackend;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::process::Command;
+
+pub const TREE_SCHEMA: &str = r#"
+{
+    "type": "record",
+    "name": "Node",
+    "fields": [
+        {"name": "id", "type": "int"},
+        {"name": "label", "type": "string"},
+        {"name": "children", "type": {"type": "array", "items": "Node"}}
+    ]
+}
+"#;
+
+/// Depths tried by `depth-stress`, each in its own process.
+pub const STRESS_DEPTHS: [usize; 6] = [10, 100, 1_000, 10_000, 100_000, 1_000_000];
+
+/// Shape of each generated tree, set with `--depth` and `--fanout`.
+#[derive(Debug, Clone, Copy)]
+pub struct TreeShape {
+    pub depth: usize,
+    pub fanout: usize,
+}
+
+impl Default for TreeShape {
+    fn default() -> Self {
+        TreeShape {
+            depth: 4,
+            fanout: 2,
+        }
+    }
+}
+
+impl TreeShape {
+    /// Nodes in one tree: a full tree of `depth` levels, or `None` if that
+    /// does not fit in a `usize`.
+    pub fn node_count(&self) -> Option<usize> {
+        (0..self.depth).try_fold(0usize, |sum, level| {
+            let width = self.fanout.checked_pow(u32::try_from(level).ok()?)?;
+            sum.checked_add(width)
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Node {
+    pub id: i32,
+    pub label: String,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    fn new(id: i32, children: Vec<Node>) -> Node {
+        Node {
+            id,
+            label: format!("node_{}", id),
+            children,
+        }
+    }
+
+    /// Nodes in this subtree, counted without recursion.
+    pub fn count(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            count += 1;
+            stack.extend(&node.children);
+        }
+        count
+    }
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Node) -> bool {
+        let mut stack = vec![(self, other)];
+        while let Some((a, b)) = stack.pop() {
+            if a.id != b.id || a.label != b.label || a.children.len() != b.children.len() {
+                return false;
+            }
+            stack.extend(a.children.iter().zip(&b.children));
+        }
+        true
+    }
+}
+
+// Summarise rather than recurse: a mismatch message only needs the root
+impl fmt::Debug for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Node {{ id: {}, label: {:?}, {} nodes }}",
+            self.id,
+            self.label,
+            self.count()
+        )
+    }
+}
+
+// The derived drop recurses once per level and overflows on deep lists
+impl Drop for Node {
+    fn drop(&mut self) {
+        let mut stack = std::mem::take(&mut self.children);
+        while let Some(mut node) = stack.pop() {
+            stack.append(&mut node.children);
+        }
+    }
+}
+
+/// First node id of tree `i`, or an error if trees `0..=i` need more ids than
+/// an Avro `int` holds.
+fn first_id(i: i32, shape: TreeShape) -> Result<i32> {
+    let nodes = shape.node_count().and_then(|n| i32::try_from(n).ok());
+    match nodes.filter(|&n| n.checked_mul(i.saturating_add(1)).is_some()) {
+        Some(n) => Ok(i * n),
+        None => Err(format!(
+            "{} trees of depth {} and fan-out {} need more node ids than an int holds",
+            i.saturating_add(1),
+            shape.depth,
+            shape.fanout
+        )
+        .into()),
+    }
+}
+
+/// Tree `i`, built bottom-up. Node ids are unique across all trees.
+pub fn create_tree(i: i32, shape: TreeShape) -> Result<Node> {
+    let mut next_id = first_id(i, shape)?;
+    let mut id = || {
+        next_id += 1;
+        next_id - 1
+    };
+
+    let leaves = shape.fanout.pow(shape.depth.saturating_sub(1) as u32);
+    let mut level: Vec<Node> = (0..leaves).map(|_| Node::new(id(), Vec::new())).collect();
+    for _ in 1..shape.depth {
+        let mut children = level.into_iter();
+        level = Vec::new();
+        loop {
+            let group: Vec<Node> = children.by_ref().take(shape.fanout).collect();
+            if group.is_empty() {
+                break;
+            }
+            level.push(Node::new(id(), group));
+        }
+    }
+    Ok(level.pop().unwrap_or_else(|| Node::new(id(), Vec::new())))
+}
+
+pub fn create_trees(count: i32, shape: TreeShape) -> Result<Vec<Node>> {
+    // The last tree has the largest ids: check it before building any
+    if count > 0 {
+        first_id(count - 1, shape)?;
+    }
+    (0..count).map(|i| create_tree(i, shape)).collect()
+}
+
+/// Round-trip one linked list of `depth` nodes through `backend`. Run by
+/// `depth_stress` in a child process.
+pub fn depth_probe<B: AvroBackend>(backend: &mut B, depth: usize) -> Result<()> {
+    let list = create_tree(0, TreeShape { depth, fanout: 1 })?;
+    let bytes = backend.encode_datum(&list)?;
+    // Lets depth_stress tell an encode failure from a decode failure
+    println!("encoded {} bytes", bytes.len());
+    let decoded: Node = backend.decode_datum(&bytes)?;
+    if decoded != list {
+        return Err(format!("depth {} did not round-trip", depth).into());
+    }
+    Ok(())
+}
+
+/// Run `depth-probe` for each of [`STRESS_DEPTHS`] in a child process and
+/// report whether it succeeds, returns an error or overflows the stack. A
+/// stack overflow aborts the whole process, so it cannot be caught in-process.
+pub fn depth_stress(implementation: &str) -> Result<()> {
+    let exe = std::env::current_exe()?;
+    println!(
+        "Depth stress for {} (linked list, main thread stack)",
+        implementation
+    );
+
+    for depth in STRESS_DEPTHS {
+        let output = Command::new(&exe)
+            .args(["depth-probe", &depth.to_string(), "--workload", "tree"])
+            .output()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        let phase = if stdout.contains("encoded") {
+            "decode"
+        } else {
+            "encode"
+        };
+
+        let outcome = if output.status.success() {
+            format!("ok ({})", stdout.trim())
+        } else if stderr.contains("overflowed its stack") {
+            format!("stack overflow during {}", phase)
+        } else if output.status.code().is_some() {
+            let message = stderr.lines().last().unwrap_or("").trim();
+            format!(
+                "clean error during {}: {}",
+                phase,
+                message.trim_start_matches("Error: ")
+            )
+        } else {
+            format!("crashed during {} ({})", phase, output.status)
+        };
+        println!("  depth {:>9}: {}", depth, outcome);
+    }
+    Ok(())
+}
@@ -0,0 +1,235 @@
+// Workloads shared by every backend: raw datum encode/decode and object
+// container write/read. The text output keeps the sentences
+// run_comparison.sh users are used to.
+
+use crate::backend::AvroBackend;
+use crate::cli::Options;
+use crate::harness::{self, Stats};
+use crate::report::{Reporter, ResultRecord};
+use crate::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+/// The record shape a benchmark run works on, selected with `--workload`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Workload {
+    /// The cross-language Person record
+    Person,
+    /// Every Avro logical type, see [`crate::logical`]
+    Logical,
+    /// Recursive Node records, see [`crate::tree`]
+    Tree,
+}
+
+impl Workload {
+    pub fn name(self) -> &'static str {
+        match self {
+            Workload::Person => "person",
+            Workload::Logical => "logical",
+            Workload::Tree => "tree",
+        }
+    }
+}
+
+impl FromStr for Workload {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "person" => Ok(Workload::Person),
+            "logical" => Ok(Workload::Logical),
+            "tree" => Ok(Workload::Tree),
+            _ => Err(format!(
+                "Unknown workload '{}' (expected person, logical or tree)",
+                s
+            )),
+        }
+    }
+}
+
+pub fn encode<B: AvroBackend, T: Serialize>(
+    backend: &mut B,
+    values: &[T],
+    options: &Options,
+    reporter: &Reporter,
+) -> Result<()> {
+    let mut total_bytes = 0;
+
+    let stats = harness::measure(&options.harness, || {
+        total_bytes = 0;
+        for value in values {
+            let bytes = backend.encode_datum(value)?;
+            total_bytes += bytes.len();
+        }
+        Ok(())
+    })?;
+
+    let record = ResultRecord::measured(
+        B::IMPLEMENTATION,
+        "encode",
+        values.len(),
+        total_bytes as u64,
+        &stats,
+    )
+    .with_workload(options.workload.name());
+    reporter.emit(std::slice::from_ref(&record), || {
+        println!(
+            "Encoded {} records in {:.6} seconds ({:.2} MB/s, {} bytes)",
+            values.len(),
+            record.elapsed_secs,
+            record.mb_per_sec,
+            total_bytes
+        );
+        print_stats(&stats, values.len());
+    });
+    Ok(())
+}
+
+pub fn decode<B: AvroBackend, T: Serialize + DeserializeOwned + PartialEq + Debug>(
+    backend: &mut B,
+    values: &[T],
+    options: &Options,
+    reporter: &Reporter,
+) -> Result<()> {
+    // Encode first
+    let encoded = values
+        .iter()
+        .map(|value| backend.encode_datum(value))
+        .collect::<Result<Vec<_>>>()?;
+
+    let total_bytes: usize = encoded.iter().map(|b| b.len()).sum();
+
+    // Round-trip check, outside the timed section
+    for (i, (value, bytes)) in values.iter().zip(&encoded).enumerate() {
+        let decoded: T = backend.decode_datum(bytes)?;
+        if decoded != *value {
+            return Err(format!(
+                "Round-trip mismatch at record {}: expected {:?}, got {:?}",
+                i, value, decoded
+            )
+            .into());
+        }
+    }
+
+    // Benchmark decode
+    let stats = harness::measure(&options.harness, || {
+        for bytes in &encoded {
+            let _value: T = backend.decode_datum(bytes)?;
+        }
+        Ok(())
+    })?;
+
+    let record = ResultRecord::measured(
+        B::IMPLEMENTATION,
+        "decode",
+        encoded.len(),
+        total_bytes as u64,
+        &stats,
+    )
+    .with_workload(options.workload.name());
+    reporter.emit(std::slice::from_ref(&record), || {
+        println!(
+            "Decoded {} records in {:.6} seconds ({:.2} MB/s, {} bytes)",
+            encoded.len(),
+            record.elapsed_secs,
+            record.mb_per_sec,
+            total_bytes
+        );
+        print_stats(&stats, encoded.len());
+    });
+    Ok(())
+}
+
+pub fn container<B: AvroBackend, T: Serialize + DeserializeOwned>(
+    backend: &mut B,
+    values: &[T],
+    options: &Options,
+    reporter: &Reporter,
+) -> Result<()> {
+    let compression = options.compression.as_str();
+    let codec = B::codec(compression)?;
+
+    let temp_dir = std::env::temp_dir();
+    let temp_path = temp_dir.join(format!("bench_{}_{}.avro", B::IMPLEMENTATION, compression));
+
+    let write_stats = harness::measure(&options.harness, || {
+        backend.write_container(values, codec, options.block_size, &temp_path)
+    })?;
+    let read_stats = harness::measure(&options.harness, || {
+        let count_read = backend.read_container::<T>(&temp_path)?;
+        if count_read != values.len() {
+            return Err(format!(
+                "read {} records back, expected {}",
+                count_read,
+                values.len()
+            )
+            .into());
+        }
+        Ok(())
+    })?;
+
+    let metadata = std::fs::metadata(&temp_path)?;
+    let file_size = metadata.len();
+
+    std::fs::remove_file(&temp_path)?;
+
+    let records = [
+        ResultRecord::measured(
+            B::IMPLEMENTATION,
+            "container-write",
+            values.len(),
+            file_size,
+            &write_stats,
+        )
+        .with_codec(compression)
+        .with_block_size(options.block_size)
+        .with_workload(options.workload.name()),
+        ResultRecord::measured(
+            B::IMPLEMENTATION,
+            "container-read",
+            values.len(),
+            file_size,
+            &read_stats,
+        )
+        .with_codec(compression)
+        .with_block_size(options.block_size)
+        .with_workload(options.workload.name()),
+    ];
+    reporter.emit(&records, || {
+        println!(
+            "Container[{}]: Wrote {} records in {:.6} seconds, Read in {:.6} seconds ({} bytes)",
+            compression,
+            values.len(),
+            write_stats.mean,
+            read_stats.mean,
+            file_size
+        );
+        println!(
+            "  Write: {:.2} MB/s, Read: {:.2} MB/s",
+            records[0].mb_per_sec, records[1].mb_per_sec
+        );
+        if write_stats.iterations > 1 {
+            println!("  write: {}", write_stats.summary());
+            println!("  read:  {}", read_stats.summary());
+        }
+        if let (Some(write), Some(read)) = (&write_stats.alloc, &read_stats.alloc) {
+            println!("  write {}", write.summary(values.len()));
+            println!("  read  {}", read.summary(values.len()));
+        }
+    });
+    Ok(())
+}
+
+/// Print the iteration summary under a result line when more than one
+/// iteration was measured, and the allocation summary when allocations were
+/// counted. `records` is the number of records handled per iteration.
+pub fn print_stats(stats: &Stats, records: usize) {
+    if stats.iterations > 1 {
+        println!("  {}", stats.summary());
+    }
+    if let Some(alloc) = &stats.alloc {
+        println!("  {}", alloc.summary(records));
+    }
+}
@@ -0,0 +1,102 @@
+// AvroBackend for the serde_avro_fast crate (high-performance alternative)
+// This uses direct serde integration without intermediate Value representation
+
+use avro_rust_bench_common::backend::CODEC_NAMES;
+use avro_rust_bench_common::{AvroBackend, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_avro_fast::object_container_file_encoding::{
+    Compression, CompressionLevel, Reader, WriterBuilder,
+};
+use serde_avro_fast::ser::SerializerConfig;
+use serde_avro_fast::Schema;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+pub struct FastBackend<'s> {
+    schema: &'s Schema,
+    // Use SerializerConfig once for all records
+    config: SerializerConfig<'s>,
+}
+
+impl<'s> FastBackend<'s> {
+    pub fn new(schema: &'s Schema) -> FastBackend<'s> {
+        FastBackend {
+            schema,
+            config: SerializerConfig::new(schema),
+        }
+    }
+}
+
+pub fn parse_schema(schema_json: &str) -> Result<Schema> {
+    Ok(schema_json.parse()?)
+}
+
+impl AvroBackend for FastBackend<'_> {
+    const IMPLEMENTATION: &'static str = "rust-serde-avro-fast";
+
+    type Codec = Compression;
+
+    fn codec(name: &str) -> Result<Compression> {
+        match name {
+            "null" => Ok(Compression::Null),
+            "deflate" => Ok(Compression::Deflate {
+                level: CompressionLevel::default(),
+            }),
+            _ if CODEC_NAMES.contains(&name) => Err(format!(
+                "Compression codec '{}' is not supported by avro-rust-bench-fast",
+                name
+            )
+            .into()),
+            _ => Err(format!(
+                "Unknown compression codec '{}' (expected one of: {})",
+                name,
+                CODEC_NAMES.join(", ")
+            )
+            .into()),
+        }
+    }
+
+    fn encode_datum<T: Serialize>(&mut self, value: &T) -> Result<Vec<u8>> {
+        // Direct encoding without intermediate Value
+        Ok(serde_avro_fast::to_datum(
+            value,
+            Vec::new(),
+            &mut self.config,
+        )?)
+    }
+
+    fn decode_datum<T: DeserializeOwned>(&mut self, bytes: &[u8]) -> Result<T> {
+        Ok(serde_avro_fast::from_datum_slice(bytes, self.schema)?)
+    }
+
+    fn write_container<T: Serialize>(
+        &mut self,
+        values: &[T],
+        codec: Compression,
+        block_size: Option<usize>,
+        path: &Path,
+    ) -> Result<()> {
+        let mut builder = WriterBuilder::new(&mut self.config).compression(codec);
+        if let Some(block_size) = block_size {
+            builder = builder.approx_block_size(u32::try_from(block_size)?);
+        }
+        let mut writer = builder.build(BufWriter::new(File::create(path)?))?;
+        writer.serialize_all(values)?;
+        writer.into_inner()?.flush()?;
+        Ok(())
+    }
+
+    // serde_avro_fast has no Value type, so records are deserialized straight
+    // into T
+    fn read_container<T: DeserializeOwned>(&mut self, path: &Path) -> Result<usize> {
+        let mut reader = Reader::from_reader(BufReader::new(File::open(path)?))?;
+        let mut count = 0;
+        for value in reader.deserialize::<T>() {
+            let _value: T = value?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
@@ -2,130 +2,100 @@
 // This uses direct serde integration without intermediate Value representation
 // Claims 10-20x faster than apache-avro
 
-use serde::{Deserialize, Serialize};
-use std::env;
-use std::time::Instant;
+mod backend;
+
+use avro_rust_bench_common::alloc::CountingAllocator;
+use avro_rust_bench_common::cli::{self, Options};
+use avro_rust_bench_common::logical::create_logical_records;
+use avro_rust_bench_common::report::Reporter;
+use avro_rust_bench_common::tree::{self, create_trees};
+use avro_rust_bench_common::workloads::Workload;
+use avro_rust_bench_common::{
+    dataset, datums, workloads, AvroBackend, LOGICAL_SCHEMA, PERSON_SCHEMA, TREE_SCHEMA,
+};
+use backend::FastBackend;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const USAGE: &str = "[encode|decode|container] [count] [compression] \
+                     [--workload person|logical|tree] [--depth N] [--fanout N] \
+                     [--warmup N] [--iterations N] [--measure-alloc] [--format text|json|csv] \
+                     [--block-size N[K|M]]
+       avro-rust-bench-fast depth-stress --workload tree
+       avro-rust-bench-fast [encode|decode|container] --dataset <people.jsonl>
+       avro-rust-bench-fast generate-dataset [count] <output> [--seed N] [--string-len MIN..MAX]
+                            [--null-ratio F] [--phones MIN..MAX]
+       avro-rust-bench-fast emit-datums [count] <output>
+       avro-rust-bench-fast compare-datums <a> <b>";
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Person {
-    name: String,
-    age: i32,
-    email: Option<String>,
-    phone_numbers: Vec<String>,
-}
-
-fn create_person(i: i32) -> Person {
-    Person {
-        name: format!("Person_{}", i),
-        age: 20 + (i % 60),
-        email: if i % 3 == 0 {
-            Some(format!("person{}@example.com", i))
-        } else {
-            None
-        },
-        phone_numbers: (0..(1 + i % 3))
-            .map(|j| format!("+1-555-{:04}", i * 10 + j))
-            .collect(),
+fn main() {
+    let options = Options::from_env(&[]).unwrap_or_else(|err| {
+        cli::usage_error("avro-rust-bench-fast", USAGE, &err);
+    });
+    if let Err(err) = options.finish() {
+        cli::usage_error(&options.program, USAGE, &err);
     }
-}
+    let reporter = Reporter::new(options.format);
 
-fn get_schema() -> serde_avro_fast::Schema {
-    let schema_str = r#"
-    {
-        "type": "record",
-        "name": "Person",
-        "fields": [
-            {"name": "name", "type": "string"},
-            {"name": "age", "type": "int"},
-            {"name": "email", "type": ["null", "string"]},
-            {"name": "phone_numbers", "type": {"type": "array", "items": "string"}}
-        ]
-    }
-    "#;
-    schema_str.parse().unwrap()
+    cli::exit_on_error(run(&options, &reporter));
 }
 
-fn benchmark_encode(count: i32) {
-    let schema = get_schema();
-    let people: Vec<Person> = (0..count).map(create_person).collect();
-
-    let start = Instant::now();
-    let mut total_bytes = 0;
-
-    // Use SerializerConfig once for all records
-    let mut config = serde_avro_fast::ser::SerializerConfig::new(&schema);
-
-    for person in &people {
-        // Direct encoding without intermediate Value
-        let bytes = serde_avro_fast::to_datum(person, Vec::new(), &mut config).unwrap();
-        total_bytes += bytes.len();
+fn run(options: &Options, reporter: &Reporter) -> avro_rust_bench_common::Result<()> {
+    match options.workload {
+        Workload::Person => run_person(options, reporter),
+        Workload::Logical => run_logical(options, reporter),
+        Workload::Tree => run_tree(options, reporter),
     }
-
-    let elapsed = start.elapsed().as_secs_f64();
-    let mb_per_sec = (total_bytes as f64 / elapsed) / 1_000_000.0;
-
-    println!(
-        "Encoded {} records in {:.6} seconds ({:.2} MB/s, {} bytes)",
-        count, elapsed, mb_per_sec, total_bytes
-    );
 }
 
-fn benchmark_decode(count: i32) {
-    let schema = get_schema();
-    let people: Vec<Person> = (0..count).map(create_person).collect();
-
-    // Encode first
-    let mut config = serde_avro_fast::ser::SerializerConfig::new(&schema);
-    let mut encoded = Vec::new();
-    for person in &people {
-        let bytes = serde_avro_fast::to_datum(person, Vec::new(), &mut config).unwrap();
-        encoded.push(bytes);
+fn run_person(options: &Options, reporter: &Reporter) -> avro_rust_bench_common::Result<()> {
+    let people = || dataset::people(options);
+    let schema = backend::parse_schema(PERSON_SCHEMA)?;
+    let mut backend = FastBackend::new(&schema);
+
+    match options.operation.as_str() {
+        "encode" => workloads::encode(&mut backend, &people()?, options, reporter),
+        "decode" => workloads::decode(&mut backend, &people()?, options, reporter),
+        "container" => workloads::container(&mut backend, &people()?, options, reporter),
+        "generate-dataset" => dataset::generate(options),
+        "emit-datums" => datums::emit(&mut backend, &people()?, options.output_path()?),
+        "compare-datums" => datums::compare(
+            options.required(1, "two datum files")?,
+            options.required(2, "two datum files")?,
+        ),
+        op => cli::usage_error(
+            &options.program,
+            USAGE,
+            &format!("Unknown operation {}", op),
+        ),
     }
+}
 
-    let total_bytes: usize = encoded.iter().map(|b| b.len()).sum();
+fn run_logical(options: &Options, reporter: &Reporter) -> avro_rust_bench_common::Result<()> {
+    let records = create_logical_records(options.count);
+    let schema = backend::parse_schema(LOGICAL_SCHEMA)?;
+    let mut backend = FastBackend::new(&schema);
 
-    // Benchmark decode
-    let start = Instant::now();
-    for bytes in &encoded {
-        let _person: Person = serde_avro_fast::from_datum_slice(bytes, &schema).unwrap();
+    match options.operation.as_str() {
+        "encode" => workloads::encode(&mut backend, &records, options, reporter),
+        "decode" => workloads::decode(&mut backend, &records, options, reporter),
+        "container" => workloads::container(&mut backend, &records, options, reporter),
+        op => Err(format!("{} does not support --workload logical", op).into()),
     }
-
-    let elapsed = start.elapsed().as_secs_f64();
-    let mb_per_sec = (total_bytes as f64 / elapsed) / 1_000_000.0;
-
-    println!(
-        "Decoded {} records in {:.6} seconds ({:.2} MB/s, {} bytes)",
-        count, elapsed, mb_per_sec, total_bytes
-    );
 }
 
-fn benchmark_container(_count: i32, _compression: &str) {
-    // serde_avro_fast doesn't support container files with headers/compression
-    // It's optimized for raw encoding/decoding only
-    eprintln!("Container file benchmarks not supported by serde_avro_fast");
-    eprintln!("This crate is optimized for raw encoding/decoding without container format");
-    std::process::exit(1);
-}
-
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    let operation = args.get(1).map(String::as_str).unwrap_or("encode");
-    let count: i32 = args
-        .get(2)
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(10000);
-    let compression = args.get(3).map(String::as_str).unwrap_or("null");
-
-    match operation {
-        "encode" => benchmark_encode(count),
-        "decode" => benchmark_decode(count),
-        "container" => benchmark_container(count, compression),
-        _ => {
-            eprintln!(
-                "Usage: {} [encode|decode|container] [count] [compression]",
-                args[0]
-            );
-            std::process::exit(1);
-        }
+fn run_tree(options: &Options, reporter: &Reporter) -> avro_rust_bench_common::Result<()> {
+    let trees = || create_trees(options.count, options.tree);
+    let schema = backend::parse_schema(TREE_SCHEMA)?;
+    let mut backend = FastBackend::new(&schema);
+
+    match options.operation.as_str() {
+        "encode" => workloads::encode(&mut backend, &trees()?, options, reporter),
+        "decode" => workloads::decode(&mut backend, &trees()?, options, reporter),
+        "container" => workloads::container(&mut backend, &trees()?, options, reporter),
+        "depth-stress" => tree::depth_stress(FastBackend::IMPLEMENTATION),
+        "depth-probe" => tree::depth_probe(&mut backend, options.required(1, "a depth")?.parse()?),
+        op => Err(format!("{} does not support --workload tree", op).into()),
     }
 }
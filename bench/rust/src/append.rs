@@ -0,0 +1,200 @@
+// Appending to an existing container (`append`)
+// Ingestion jobs add batches to a file that is already there. The header is
+// read back first: the new blocks must use the file's codec and end with its
+// sync marker, and their records must be encoded with its schema, so the
+// records' schema has to have the same Parsing Canonical Form. A Writer
+// created with that marker and has_header set then writes only blocks, and
+// the combined file is scanned and read back in full.
+
+use crate::backend::ApacheBackend;
+use crate::parallel::{summarize, Summary};
+use apache_avro::schema_compatibility::SchemaCompatibility;
+use apache_avro::{from_value, Reader, Schema, Writer};
+use avro_rust_bench_common::cli::Options;
+use avro_rust_bench_common::fingerprint::parsing_canonical_form;
+use avro_rust_bench_common::harness;
+use avro_rust_bench_common::report::{Reporter, ResultRecord};
+use avro_rust_bench_common::workloads::print_stats;
+use avro_rust_bench_common::{create_person, dataset, AvroBackend, Person, Result, PERSON_SCHEMA};
+use std::fs::{File, OpenOptions};
+use std::io::BufWriter;
+use std::path::Path;
+
+// Appended blocks are decoded with the file's schema, so only an identical
+// one will do; an evolved schema needs a new file
+fn check_schema(existing: &Schema, records: &Schema) -> Result<()> {
+    let existing_form = parsing_canonical_form(&serde_json::to_string(existing)?)?;
+    if existing_form == parsing_canonical_form(PERSON_SCHEMA)? {
+        return Ok(());
+    }
+    let reason = match SchemaCompatibility::can_read(records, existing) {
+        Ok(()) => {
+            "Person can be resolved against it, but a container has one writer schema".to_string()
+        }
+        Err(err) => format!("Person is not compatible with it: {}", err),
+    };
+    Err(format!(
+        "cannot append Person records to a file with schema {} ({})",
+        existing_form, reason
+    )
+    .into())
+}
+
+fn append_batch(
+    existing: &Summary,
+    batch: &[Person],
+    block_size: Option<usize>,
+    path: &Path,
+) -> Result<()> {
+    let file = OpenOptions::new().append(true).open(path)?;
+    let mut writer = Writer::builder()
+        .schema(&existing.schema)
+        .writer(BufWriter::new(file))
+        .codec(existing.codec)
+        .marker(existing.sync)
+        .has_header(true)
+        .maybe_block_size(block_size)
+        .build();
+    for person in batch {
+        writer.append_ser(person)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// The original records come first when the bench wrote them, then the batch
+fn check_combined(
+    path: &Path,
+    existing: &Summary,
+    original: Option<&[Person]>,
+    batch: &[Person],
+) -> Result<Summary> {
+    let combined = summarize(&std::fs::read(path)?)?;
+    let expected = existing.records + batch.len();
+    if combined.records != expected {
+        return Err(format!(
+            "combined file has {} records in its blocks, expected {}",
+            combined.records, expected
+        )
+        .into());
+    }
+    let mut read = 0;
+    for (i, value) in Reader::new(File::open(path)?)?.enumerate() {
+        let value = value?;
+        read += 1;
+        let want = match i.checked_sub(existing.records) {
+            Some(j) => &batch[j],
+            None => match original {
+                Some(original) => &original[i],
+                None => continue,
+            },
+        };
+        if from_value::<Person>(&value)? != *want {
+            return Err(
+                format!("combined file record {} differs from the record written", i).into(),
+            );
+        }
+    }
+    if read != expected {
+        return Err(format!("Reader found {} records, expected {}", read, expected).into());
+    }
+    Ok(combined)
+}
+
+/// `append [count] [compression] [--input <file.avro>] [--output <file.avro>]`
+///
+/// Without `--input`, a Person container of `count` records is written first
+/// with the given codec. Either way `count` more records are appended to a
+/// copy of it, continuing `create_person` from the records already there, so
+/// the combined file still passes `verify`. With `--dataset`, the dataset's
+/// records are appended instead.
+pub fn benchmark_append(
+    input: Option<&str>,
+    output: Option<&str>,
+    options: &Options,
+    reporter: &Reporter,
+) -> Result<()> {
+    let people = dataset::people(options)?;
+    let path = std::env::temp_dir().join(format!(
+        "bench_{}_append_{}.avro",
+        ApacheBackend::IMPLEMENTATION,
+        options.compression
+    ));
+
+    let base = match input {
+        Some(input) => std::fs::read(input)?,
+        None => {
+            let mut backend = ApacheBackend::new(PERSON_SCHEMA)?;
+            let codec = ApacheBackend::codec(&options.compression)?;
+            backend.write_container(&people, codec, options.block_size, &path)?;
+            std::fs::read(&path)?
+        }
+    };
+    let existing = summarize(&base)?;
+    check_schema(&existing.schema, &Schema::parse_str(PERSON_SCHEMA)?)?;
+    let batch: Vec<Person> = match options.dataset {
+        Some(_) => people.clone(),
+        None => (0..options.count)
+            .map(|i| create_person(existing.records as i32 + i))
+            .collect(),
+    };
+
+    // Each iteration appends to a fresh copy of the base, restored untimed
+    let stats = harness::measure_with_setup(
+        &options.harness,
+        || Ok(std::fs::write(&path, &base)?),
+        |()| append_batch(&existing, &batch, options.block_size, &path),
+    )?;
+    let original = input.is_none().then_some(&people[..]);
+    let combined = check_combined(&path, &existing, original, &batch)?;
+    let combined_size = std::fs::metadata(&path)?.len();
+    // Copied rather than renamed, as the temp dir may be another filesystem
+    if let Some(output) = output {
+        std::fs::copy(&path, output)?;
+    }
+    std::fs::remove_file(&path)?;
+
+    let codec_name: &str = existing.codec.into();
+    let appended_bytes = combined_size - base.len() as u64;
+    let records = [ResultRecord::measured(
+        ApacheBackend::IMPLEMENTATION,
+        "container-append",
+        batch.len(),
+        appended_bytes,
+        &stats,
+    )
+    .with_codec(codec_name)
+    .with_block_size(options.block_size)];
+
+    reporter.emit(&records, || {
+        println!(
+            "=== Append to Container ({} existing + {} new records, {}) ===",
+            existing.records,
+            batch.len(),
+            codec_name
+        );
+        println!(
+            "Existing: {} bytes, {} blocks{}",
+            base.len(),
+            existing.blocks,
+            input.map_or(String::new(), |input| format!(" ({})", input))
+        );
+        println!(
+            "Appended {} records in {:.6} seconds ({:.2} MB/s, {} bytes, {} blocks)",
+            batch.len(),
+            stats.mean,
+            records[0].mb_per_sec,
+            appended_bytes,
+            combined.blocks - existing.blocks
+        );
+        print_stats(&stats, batch.len());
+        println!(
+            "Combined: {} records in {} blocks, every one ending with the original sync marker; read back with apache_avro::Reader",
+            combined.records, combined.blocks
+        );
+        if let Some(output) = output {
+            println!("Kept the combined file as {}", output);
+        }
+    });
+    Ok(())
+}
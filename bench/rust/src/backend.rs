@@ -0,0 +1,132 @@
+// AvroBackend for the apache-avro crate (official Apache implementation)
+// This uses the standard Value-based approach which has inherent overhead
+
+use apache_avro::{from_value, to_value, Codec, Reader, Schema, Writer};
+use avro_rust_bench_common::backend::CODEC_NAMES;
+use avro_rust_bench_common::{AvroBackend, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+pub struct ApacheBackend {
+    schema: Schema,
+}
+
+impl ApacheBackend {
+    pub fn new(schema_json: &str) -> Result<ApacheBackend> {
+        Ok(ApacheBackend {
+            schema: Schema::parse_str(schema_json)?,
+        })
+    }
+}
+
+impl AvroBackend for ApacheBackend {
+    const IMPLEMENTATION: &'static str = "rust-apache-avro";
+
+    type Codec = Codec;
+
+    fn codec(name: &str) -> Result<Codec> {
+        match name {
+            "null" => Ok(Codec::Null),
+            "deflate" => Ok(Codec::Deflate(Default::default())),
+            #[cfg(feature = "snappy")]
+            "snappy" => Ok(Codec::Snappy),
+            #[cfg(feature = "zstandard")]
+            "zstandard" => Ok(Codec::Zstandard(Default::default())),
+            #[cfg(feature = "bzip")]
+            "bzip2" => Ok(Codec::Bzip2(Default::default())),
+            #[cfg(feature = "xz")]
+            "xz" => Ok(Codec::Xz(Default::default())),
+            #[cfg(not(feature = "snappy"))]
+            "snappy" => Err(codec_disabled(name, "snappy")),
+            #[cfg(not(feature = "zstandard"))]
+            "zstandard" => Err(codec_disabled(name, "zstandard")),
+            #[cfg(not(feature = "bzip"))]
+            "bzip2" => Err(codec_disabled(name, "bzip")),
+            #[cfg(not(feature = "xz"))]
+            "xz" => Err(codec_disabled(name, "xz")),
+            _ => Err(format!(
+                "Unknown compression codec '{}' (expected one of: {})",
+                name,
+                CODEC_NAMES.join(", ")
+            )
+            .into()),
+        }
+    }
+
+    fn encode_datum<T: Serialize>(&mut self, value: &T) -> Result<Vec<u8>> {
+        // NOTE: apache-avro crate has inherent performance limitations:
+        // 1. to_value() creates intermediate Value representation (serde overhead)
+        // 2. to_avro_datum() then encodes Value to bytes
+        // This two-step process is ~10-20x slower than direct encoding
+        // (`avro-rust-bench encode --phases` times each step separately)
+        // Alternative crates like serde_avro_fast claim 10-20x speedup by avoiding Value
+        let value = to_value(value)?;
+        Ok(apache_avro::to_avro_datum(&self.schema, value)?)
+    }
+
+    fn decode_datum<T: DeserializeOwned>(&mut self, bytes: &[u8]) -> Result<T> {
+        let value = apache_avro::from_avro_datum(&self.schema, &mut &bytes[..], None)?;
+        Ok(from_value(&value)?)
+    }
+
+    fn write_container<T: Serialize>(
+        &mut self,
+        values: &[T],
+        codec: Codec,
+        block_size: Option<usize>,
+        path: &Path,
+    ) -> Result<()> {
+        let mut writer = Writer::builder()
+            .schema(&self.schema)
+            .writer(BufWriter::new(File::create(path)?))
+            .codec(codec)
+            .maybe_block_size(block_size)
+            .build();
+        for value in values {
+            writer.append_ser(value)?;
+        }
+        // Writer::flush skips the inner writer when no values are pending
+        writer.into_inner()?.flush()?;
+        Ok(())
+    }
+
+    fn read_container<T: DeserializeOwned>(&mut self, path: &Path) -> Result<usize> {
+        let reader = Reader::new(BufReader::new(File::open(path)?))?;
+        let mut count = 0;
+        for value in reader {
+            let _value: T = from_value(&value?)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+/// Whether `schema` refers to a named type by name. Only then does encoding or
+/// decoding need the schema's names resolved.
+pub(crate) fn has_refs(schema: &Schema) -> bool {
+    match schema {
+        Schema::Ref { .. } => true,
+        Schema::Array(array) => has_refs(&array.items),
+        Schema::Map(map) => has_refs(&map.types),
+        Schema::Union(union) => union.variants().iter().any(has_refs),
+        Schema::Record(record) => record.fields.iter().any(|field| has_refs(&field.schema)),
+        _ => false,
+    }
+}
+
+#[cfg(not(all(
+    feature = "snappy",
+    feature = "zstandard",
+    feature = "bzip",
+    feature = "xz"
+)))]
+fn codec_disabled(name: &str, feature: &str) -> avro_rust_bench_common::Error {
+    format!(
+        "Compression codec '{}' requires building with the '{}' cargo feature",
+        name, feature
+    )
+    .into()
+}
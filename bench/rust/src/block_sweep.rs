@@ -0,0 +1,137 @@
+// Container block size sweep (`block-sweep`)
+// A block is flushed once its uncompressed size reaches the writer's block
+// size. Larger blocks give the codec more context and cut the per-block
+// header and sync marker overhead, but the writer and reader each hold a
+// whole block in memory. The same records are written at each size with
+// every codec compiled into this build; allocations are always counted so
+// the table can show peak heap next to file size and timings.
+
+use crate::backend::ApacheBackend;
+use crate::parallel::summarize;
+use avro_rust_bench_common::backend::CODEC_NAMES;
+use avro_rust_bench_common::cli::Options;
+use avro_rust_bench_common::harness::{self, HarnessConfig, Stats};
+use avro_rust_bench_common::report::{Reporter, ResultRecord};
+use avro_rust_bench_common::{AvroBackend, Person, Result, PERSON_SCHEMA};
+
+const BLOCK_SIZES: [usize; 5] = [4 << 10, 16 << 10, 64 << 10, 256 << 10, 1 << 20];
+
+struct Row {
+    block_size: usize,
+    blocks: usize,
+    file_size: u64,
+    write: Stats,
+    read: Stats,
+}
+
+fn size_label(bytes: usize) -> String {
+    if bytes.is_multiple_of(1 << 20) {
+        format!("{} MiB", bytes >> 20)
+    } else {
+        format!("{} KiB", bytes >> 10)
+    }
+}
+
+fn peak_heap(stats: &Stats) -> String {
+    stats.alloc.as_ref().map_or_else(
+        || "-".to_string(),
+        |alloc| format!("{:.1} KiB", alloc.peak_heap_bytes as f64 / 1024.0),
+    )
+}
+
+/// `block-sweep [count]`
+pub fn benchmark_block_sweep(
+    people: &[Person],
+    options: &Options,
+    reporter: &Reporter,
+) -> Result<()> {
+    let mut backend = ApacheBackend::new(PERSON_SCHEMA)?;
+    let count = people.len();
+    let harness = HarnessConfig {
+        measure_alloc: true,
+        ..options.harness
+    };
+    let temp_dir = std::env::temp_dir();
+
+    if reporter.is_text() {
+        println!("=== Container Block Size Sweep ({} records) ===", count);
+    }
+
+    for name in CODEC_NAMES {
+        // Skip codecs whose cargo feature is disabled in this build
+        let Ok(codec) = ApacheBackend::codec(name) else {
+            continue;
+        };
+        let mut rows = Vec::new();
+        for block_size in BLOCK_SIZES {
+            let temp_path =
+                temp_dir.join(format!("bench_block_sweep_{}_{}.avro", name, block_size));
+            let write = harness::measure(&harness, || {
+                backend.write_container(people, codec, Some(block_size), &temp_path)
+            })?;
+            let read = harness::measure(&harness, || {
+                let count_read = backend.read_container::<Person>(&temp_path)?;
+                if count_read != count {
+                    return Err(format!(
+                        "{} at {} byte blocks: read {} records back, expected {}",
+                        name, block_size, count_read, count
+                    )
+                    .into());
+                }
+                Ok(())
+            })?;
+            let bytes = std::fs::read(&temp_path)?;
+            std::fs::remove_file(&temp_path)?;
+            rows.push(Row {
+                block_size,
+                blocks: summarize(&bytes)?.blocks,
+                file_size: bytes.len() as u64,
+                write,
+                read,
+            });
+        }
+
+        let records: Vec<ResultRecord> = rows
+            .iter()
+            .flat_map(|row| {
+                [
+                    ("container-write", &row.write),
+                    ("container-read", &row.read),
+                ]
+                .map(|(operation, stats)| {
+                    ResultRecord::measured(
+                        ApacheBackend::IMPLEMENTATION,
+                        operation,
+                        count,
+                        row.file_size,
+                        stats,
+                    )
+                    .with_codec(name)
+                    .with_block_size(Some(row.block_size))
+                })
+            })
+            .collect();
+
+        reporter.emit(&records, || {
+            println!();
+            println!("Block sizes[{}]: {} records", name, count);
+            println!(
+                "  {:>10} {:>7} {:>12} {:>11} {:>11} {:>12} {:>12}",
+                "block", "blocks", "file bytes", "write s", "read s", "write peak", "read peak"
+            );
+            for row in &rows {
+                println!(
+                    "  {:>10} {:>7} {:>12} {:>11.6} {:>11.6} {:>12} {:>12}",
+                    size_label(row.block_size),
+                    row.blocks,
+                    row.file_size,
+                    row.write.mean,
+                    row.read.mean,
+                    peak_heap(&row.write),
+                    peak_heap(&row.read)
+                );
+            }
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,98 @@
+// Compression codec sweep
+// Mirrors compression_bench.ml: every codec compiled into this build writes and
+// reads the same records, and sizes are compared against the null codec.
+
+use crate::backend::ApacheBackend;
+use avro_rust_bench_common::backend::CODEC_NAMES;
+use avro_rust_bench_common::cli::Options;
+use avro_rust_bench_common::harness;
+use avro_rust_bench_common::report::{Reporter, ResultRecord};
+use avro_rust_bench_common::{AvroBackend, Person, Result, PERSON_SCHEMA};
+
+pub fn benchmark_compression(
+    people: &[Person],
+    options: &Options,
+    reporter: &Reporter,
+) -> Result<()> {
+    let mut backend = ApacheBackend::new(PERSON_SCHEMA)?;
+    let count = people.len();
+    let temp_dir = std::env::temp_dir();
+
+    if reporter.is_text() {
+        println!("=== Compression Codec Comparison ({} records) ===", count);
+    }
+
+    let mut null_size = None;
+    for name in CODEC_NAMES {
+        // Skip codecs whose cargo feature is disabled in this build
+        let Ok(codec) = ApacheBackend::codec(name) else {
+            continue;
+        };
+        let temp_path = temp_dir.join(format!("bench_compression_{}.avro", name));
+
+        let write_stats = harness::measure(&options.harness, || {
+            backend.write_container(people, codec, options.block_size, &temp_path)
+        })?;
+        let file_size = std::fs::metadata(&temp_path)?.len();
+        let read_stats = harness::measure(&options.harness, || {
+            let count_read = backend.read_container::<Person>(&temp_path)?;
+            if count_read != count {
+                return Err(format!(
+                    "{}: read {} records back, expected {}",
+                    name, count_read, count
+                )
+                .into());
+            }
+            Ok(())
+        })?;
+        std::fs::remove_file(&temp_path)?;
+
+        let write = ResultRecord::measured(
+            ApacheBackend::IMPLEMENTATION,
+            "container-write",
+            count,
+            file_size,
+            &write_stats,
+        )
+        .with_codec(name)
+        .with_block_size(options.block_size);
+        let read = ResultRecord::measured(
+            ApacheBackend::IMPLEMENTATION,
+            "container-read",
+            count,
+            file_size,
+            &read_stats,
+        )
+        .with_codec(name)
+        .with_block_size(options.block_size);
+        let uncompressed_size = *null_size.get_or_insert(file_size);
+
+        reporter.emit(&[write.clone(), read.clone()], || {
+            println!();
+            println!("Compression[{}]: {} records", name, count);
+            if name == "null" {
+                println!("  File size:    {} bytes", file_size);
+            } else {
+                let compression_ratio = uncompressed_size as f64 / file_size as f64;
+                println!("  Uncompressed: {} bytes", uncompressed_size);
+                println!(
+                    "  Compressed:   {} bytes ({:.2}x compression)",
+                    file_size, compression_ratio
+                );
+            }
+            println!(
+                "  Write: {:.6} seconds ({:.2} MB/s)",
+                write.elapsed_secs, write.mb_per_sec
+            );
+            println!(
+                "  Read:  {:.6} seconds ({:.2} MB/s)",
+                read.elapsed_secs, read.mb_per_sec
+            );
+            if let (Some(write), Some(read)) = (&write_stats.alloc, &read_stats.alloc) {
+                println!("  Write {}", write.summary(count));
+                println!("  Read  {}", read.summary(count));
+            }
+        });
+    }
+    Ok(())
+}
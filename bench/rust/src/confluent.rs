@@ -0,0 +1,566 @@
+// Confluent wire format (`confluent`)
+// Kafka messages framed the way Confluent's serializers do: a zero magic byte,
+// the 4-byte big-endian schema id, then the datum. Schema ids come from a
+// local stand-in for the schema registry, served over HTTP on a loopback port,
+// so the bench exercises a real client without a live registry. The client
+// caches ids and schemas as Confluent's does, and every timed pass starts with
+// a cold cache, so each pass pays for one round trip per schema.
+
+use crate::backend::ApacheBackend;
+use apache_avro::types::Value;
+use apache_avro::{from_avro_datum, from_value, to_avro_datum, to_value, Schema};
+use avro_rust_bench_common::cli::Options;
+use avro_rust_bench_common::dataset;
+use avro_rust_bench_common::fingerprint::parsing_canonical_form;
+use avro_rust_bench_common::harness::{self, Stats};
+use avro_rust_bench_common::report::{Reporter, ResultRecord};
+use avro_rust_bench_common::workloads::print_stats;
+use avro_rust_bench_common::{
+    AvroBackend, Person, Result, LOGICAL_SCHEMA, PERSON_SCHEMA, TREE_SCHEMA,
+};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const MAGIC: u8 = 0;
+const HEADER_LEN: usize = 5;
+const PERSON_SUBJECT: &str = "person-value";
+
+fn frame(id: u32, datum: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(HEADER_LEN + datum.len());
+    message.push(MAGIC);
+    message.extend_from_slice(&id.to_be_bytes());
+    message.extend_from_slice(datum);
+    message
+}
+
+fn unframe(message: &[u8]) -> Result<(u32, &[u8])> {
+    if message.len() < HEADER_LEN {
+        return Err(format!(
+            "Confluent message is {} bytes, shorter than its {} byte header",
+            message.len(),
+            HEADER_LEN
+        )
+        .into());
+    }
+    if message[0] != MAGIC {
+        return Err(format!(
+            "not a Confluent message: magic byte {:02x}, expected 00",
+            message[0]
+        )
+        .into());
+    }
+    let id = u32::from_be_bytes(message[1..HEADER_LEN].try_into().expect("4 byte id"));
+    Ok((id, &message[HEADER_LEN..]))
+}
+
+// Minimal HTTP/1.1: one request per connection, bodies sized by Content-Length
+
+struct HttpMessage {
+    start_line: String,
+    body: String,
+}
+
+fn read_http(stream: &TcpStream) -> Result<HttpMessage> {
+    let mut reader = BufReader::new(stream);
+    let mut start_line = String::new();
+    reader.read_line(&mut start_line)?;
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse()?;
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(HttpMessage {
+        start_line: start_line.trim_end().to_string(),
+        body: String::from_utf8(body)?,
+    })
+}
+
+#[derive(Default)]
+struct RegistryState {
+    /// Schema text by id, ids starting at 1
+    schemas: Vec<String>,
+    /// Canonical form to id, so re-registering a schema returns its id
+    ids: HashMap<String, u32>,
+    /// Ids of each subject's versions, oldest first
+    subjects: BTreeMap<String, Vec<u32>>,
+}
+
+/// The parts of the schema registry REST API the client needs:
+/// `GET /subjects`, `GET /subjects/{subject}/versions`,
+/// `GET /subjects/{subject}/versions/{version|latest}`,
+/// `POST /subjects/{subject}/versions` (register),
+/// `POST /subjects/{subject}` (look up) and `GET /schemas/ids/{id}`.
+struct MockRegistry {
+    addr: SocketAddr,
+}
+
+impl MockRegistry {
+    /// Serve on a free loopback port. The thread lives until the process exits.
+    fn start() -> Result<MockRegistry> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(RegistryState::default()));
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // A broken connection only fails that one request
+                let _ = serve(&stream, &state);
+            }
+        });
+        Ok(MockRegistry { addr })
+    }
+}
+
+fn serve(mut stream: &TcpStream, state: &Mutex<RegistryState>) -> Result<()> {
+    let request = read_http(stream)?;
+    let mut parts = request.start_line.split(' ');
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+    let (status, body) = route(method, path, &request.body, &mut state.lock().unwrap());
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/vnd.schemaregistry.v1+json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
+
+fn not_found(error_code: u32, message: &str) -> (&'static str, serde_json::Value) {
+    (
+        "404 Not Found",
+        json!({"error_code": error_code, "message": message}),
+    )
+}
+
+fn invalid_schema() -> (&'static str, serde_json::Value) {
+    (
+        "422 Unprocessable Entity",
+        json!({"error_code": 42201, "message": "Invalid schema"}),
+    )
+}
+
+// The schema of a {"schema": "..."} request, with its canonical form
+fn schema_body(body: &str) -> Option<(String, String)> {
+    let request: serde_json::Value = serde_json::from_str(body).ok()?;
+    let schema = request["schema"].as_str()?;
+    Some((parsing_canonical_form(schema).ok()?, schema.to_string()))
+}
+
+fn route(
+    method: &str,
+    path: &str,
+    body: &str,
+    state: &mut RegistryState,
+) -> (&'static str, serde_json::Value) {
+    const OK: &str = "200 OK";
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let version_of = |state: &RegistryState, subject: &str, version: usize| {
+        let id = state.subjects[subject][version - 1];
+        json!({
+            "subject": subject,
+            "version": version,
+            "id": id,
+            "schema": state.schemas[id as usize - 1],
+        })
+    };
+
+    match (method, segments.as_slice()) {
+        ("GET", ["subjects"]) => (OK, json!(state.subjects.keys().collect::<Vec<_>>())),
+        ("GET", ["schemas", "ids", id]) => match id.parse::<usize>() {
+            Ok(id) if (1..=state.schemas.len()).contains(&id) => {
+                (OK, json!({"schema": state.schemas[id - 1]}))
+            }
+            _ => not_found(40403, "Schema not found"),
+        },
+        ("GET", ["subjects", subject, "versions"]) => match state.subjects.get(*subject) {
+            Some(ids) => (OK, json!((1..=ids.len()).collect::<Vec<_>>())),
+            None => not_found(40401, "Subject not found"),
+        },
+        ("GET", ["subjects", subject, "versions", version]) => {
+            let Some(ids) = state.subjects.get(*subject) else {
+                return not_found(40401, "Subject not found");
+            };
+            let version = match *version {
+                "latest" => ids.len(),
+                version => version.parse().unwrap_or(0),
+            };
+            if (1..=ids.len()).contains(&version) {
+                (OK, version_of(state, subject, version))
+            } else {
+                not_found(40402, "Version not found")
+            }
+        }
+        ("POST", ["subjects", subject, "versions"]) => {
+            let Some((canonical, schema)) = schema_body(body) else {
+                return invalid_schema();
+            };
+            let id = match state.ids.get(&canonical) {
+                Some(&id) => id,
+                None => {
+                    state.schemas.push(schema);
+                    let id = state.schemas.len() as u32;
+                    state.ids.insert(canonical, id);
+                    id
+                }
+            };
+            let ids = state.subjects.entry(subject.to_string()).or_default();
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+            (OK, json!({ "id": id }))
+        }
+        ("POST", ["subjects", subject]) => {
+            let Some((canonical, _)) = schema_body(body) else {
+                return invalid_schema();
+            };
+            let Some(ids) = state.subjects.get(*subject) else {
+                return not_found(40401, "Subject not found");
+            };
+            let version = state
+                .ids
+                .get(&canonical)
+                .and_then(|id| ids.iter().position(|v| v == id));
+            match version {
+                Some(i) => (OK, version_of(state, subject, i + 1)),
+                None => not_found(40403, "Schema not found"),
+            }
+        }
+        _ => not_found(404, "Not found"),
+    }
+}
+
+/// Cache hits and misses (registry round trips) of one client.
+#[derive(Debug, Default, Clone, Copy)]
+struct CacheStats {
+    hits: u64,
+    misses: u64,
+}
+
+impl CacheStats {
+    fn add(&mut self, other: CacheStats) {
+        self.hits += other.hits;
+        self.misses += other.misses;
+    }
+
+    /// Share of lookups served from the cache, 0 to 1.
+    fn hit_rate(&self) -> f64 {
+        self.hits as f64 / (self.hits + self.misses).max(1) as f64
+    }
+}
+
+/// Cache statistics summed over the measured passes only; the harness runs
+/// the warmup passes first, and those are skipped.
+struct MeasuredCache {
+    warmup_left: usize,
+    total: CacheStats,
+}
+
+impl MeasuredCache {
+    fn new(warmup: usize) -> MeasuredCache {
+        MeasuredCache {
+            warmup_left: warmup,
+            total: CacheStats::default(),
+        }
+    }
+
+    fn add(&mut self, pass: CacheStats) {
+        match self.warmup_left {
+            0 => self.total.add(pass),
+            _ => self.warmup_left -= 1,
+        }
+    }
+}
+
+/// Registry client with the id and schema caches of Confluent's serializers.
+struct RegistryClient {
+    addr: SocketAddr,
+    /// Ids by subject, then schema text, as a subject can hold several schemas
+    ids: HashMap<String, HashMap<String, u32>>,
+    schemas: HashMap<u32, Schema>,
+    stats: CacheStats,
+}
+
+impl RegistryClient {
+    fn new(addr: SocketAddr) -> RegistryClient {
+        RegistryClient {
+            addr,
+            ids: HashMap::new(),
+            schemas: HashMap::new(),
+            stats: CacheStats::default(),
+        }
+    }
+
+    fn request(&self, method: &str, path: &str, body: &str) -> Result<serde_json::Value> {
+        let mut stream = TcpStream::connect(self.addr)?;
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/vnd.schemaregistry.v1+json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method,
+            path,
+            self.addr,
+            body.len(),
+            body
+        )?;
+        let response = read_http(&stream)?;
+        let json: serde_json::Value = serde_json::from_str(&response.body)?;
+        if !response.start_line.contains(" 200 ") {
+            return Err(format!(
+                "registry {} {}: {} (error code {})",
+                method, path, json["message"], json["error_code"]
+            )
+            .into());
+        }
+        Ok(json)
+    }
+
+    /// Register `schema` under `subject`, or return its id if it already is.
+    fn register(&mut self, subject: &str, schema: &str) -> Result<u32> {
+        if let Some(&id) = self.ids.get(subject).and_then(|ids| ids.get(schema)) {
+            self.stats.hits += 1;
+            return Ok(id);
+        }
+        self.stats.misses += 1;
+        let body = json!({ "schema": schema }).to_string();
+        let response = self.request("POST", &format!("/subjects/{}/versions", subject), &body)?;
+        let id = response["id"]
+            .as_u64()
+            .ok_or("registry response without an id")? as u32;
+        self.ids
+            .entry(subject.to_string())
+            .or_default()
+            .insert(schema.to_string(), id);
+        Ok(id)
+    }
+
+    fn schema(&mut self, id: u32) -> Result<&Schema> {
+        if self.schemas.contains_key(&id) {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+            let response = self.request("GET", &format!("/schemas/ids/{}", id), "")?;
+            let text = response["schema"]
+                .as_str()
+                .ok_or("registry response without a schema")?;
+            self.schemas.insert(id, Schema::parse_str(text)?);
+        }
+        Ok(&self.schemas[&id])
+    }
+
+    fn decode(&mut self, message: &[u8]) -> Result<Value> {
+        let (id, mut datum) = unframe(message)?;
+        Ok(from_avro_datum(self.schema(id)?, &mut datum, None)?)
+    }
+}
+
+fn encode_datum(schema: &Schema, person: &Person) -> Result<Vec<u8>> {
+    Ok(to_avro_datum(schema, to_value(person)?)?)
+}
+
+// What a producer does per record: look up the subject's id, encode, frame
+fn encode_messages(
+    client: &mut RegistryClient,
+    schema: &Schema,
+    people: &[Person],
+) -> Result<Vec<Vec<u8>>> {
+    people
+        .iter()
+        .map(|person| {
+            let id = client.register(PERSON_SUBJECT, PERSON_SCHEMA)?;
+            Ok(frame(id, &encode_datum(schema, person)?))
+        })
+        .collect()
+}
+
+// Unknown ids and a bad magic byte must fail cleanly. Returns the error an
+// unknown id gets, for the report.
+fn check_rejection(addr: SocketAddr, message: &[u8]) -> Result<String> {
+    let mut unknown = message.to_vec();
+    unknown[1..HEADER_LEN].copy_from_slice(&u32::MAX.to_be_bytes());
+    let mut bad_magic = message.to_vec();
+    bad_magic[0] = 0xc3;
+    let mut errors = Vec::new();
+    for (what, broken) in [
+        ("an unknown schema id", unknown),
+        ("a bad magic byte", bad_magic),
+    ] {
+        match RegistryClient::new(addr).decode(&broken) {
+            Ok(_) => return Err(format!("a message with {} was accepted", what).into()),
+            Err(err) => errors.push(err.to_string()),
+        }
+    }
+    Ok(errors.swap_remove(0))
+}
+
+pub fn benchmark_confluent(options: &Options, reporter: &Reporter) -> Result<()> {
+    let people = dataset::people(options)?;
+    let schema = Schema::parse_str(PERSON_SCHEMA)?;
+    let registry = MockRegistry::start()?;
+
+    // Other subjects, so ids are not trivially 1
+    let mut setup = RegistryClient::new(registry.addr);
+    setup.register("logical-value", LOGICAL_SCHEMA)?;
+    let tree_id = setup.register("tree-value", TREE_SCHEMA)?;
+    // A second schema under a subject gets its own id, cached separately
+    let tree_v2_id = setup.register("tree-value", LOGICAL_SCHEMA)?;
+    if tree_v2_id == tree_id || setup.register("tree-value", TREE_SCHEMA)? != tree_id {
+        return Err("client cache mixed up two schemas of one subject".into());
+    }
+    let id = setup.register(PERSON_SUBJECT, PERSON_SCHEMA)?;
+    let subjects = setup.request("GET", "/subjects", "")?;
+
+    let mut encode_cache = MeasuredCache::new(options.harness.warmup);
+    let encode_stats = harness::measure(&options.harness, || {
+        people
+            .iter()
+            .map(|person| encode_datum(&schema, person))
+            .collect::<Result<Vec<_>>>()
+    })?;
+    let encode_framed_stats = harness::measure(&options.harness, || {
+        let mut client = RegistryClient::new(registry.addr);
+        let messages = encode_messages(&mut client, &schema, &people);
+        encode_cache.add(client.stats);
+        messages
+    })?;
+
+    let datums = people
+        .iter()
+        .map(|person| encode_datum(&schema, person))
+        .collect::<Result<Vec<_>>>()?;
+    let messages = encode_messages(&mut RegistryClient::new(registry.addr), &schema, &people)?;
+    let rejection = match messages.first() {
+        Some(message) => check_rejection(registry.addr, message)?,
+        None => return Err("confluent needs at least one record".into()),
+    };
+
+    // Round-trip check, outside the timed section
+    let mut client = RegistryClient::new(registry.addr);
+    for (i, (person, message)) in people.iter().zip(&messages).enumerate() {
+        let decoded: Person = from_value(&client.decode(message)?)?;
+        if decoded != *person {
+            return Err(format!(
+                "Round-trip mismatch at record {}: expected {:?}, got {:?}",
+                i, person, decoded
+            )
+            .into());
+        }
+    }
+
+    let decode_stats = harness::measure(&options.harness, || {
+        for datum in &datums {
+            let _person: Person = from_value(&from_avro_datum(&schema, &mut &datum[..], None)?)?;
+        }
+        Ok(())
+    })?;
+    let mut decode_cache = MeasuredCache::new(options.harness.warmup);
+    let decode_framed_stats = harness::measure(&options.harness, || {
+        let mut client = RegistryClient::new(registry.addr);
+        let decoded = messages.iter().try_for_each(|message| {
+            let _person: Person = from_value(&client.decode(message)?)?;
+            Ok(())
+        });
+        decode_cache.add(client.stats);
+        decoded
+    })?;
+
+    let datum_bytes: usize = datums.iter().map(Vec::len).sum();
+    let message_bytes: usize = messages.iter().map(Vec::len).sum();
+    let results: [(&str, usize, &Stats, Option<CacheStats>); 4] = [
+        (
+            "confluent-baseline-encode",
+            datum_bytes,
+            &encode_stats,
+            None,
+        ),
+        (
+            "confluent-encode",
+            message_bytes,
+            &encode_framed_stats,
+            Some(encode_cache.total),
+        ),
+        (
+            "confluent-baseline-decode",
+            datum_bytes,
+            &decode_stats,
+            None,
+        ),
+        (
+            "confluent-decode",
+            message_bytes,
+            &decode_framed_stats,
+            Some(decode_cache.total),
+        ),
+    ];
+    let records: Vec<ResultRecord> = results
+        .iter()
+        .map(|(operation, bytes, stats, cache)| {
+            ResultRecord::measured(
+                ApacheBackend::IMPLEMENTATION,
+                operation,
+                people.len(),
+                *bytes as u64,
+                stats,
+            )
+            .with_cache_hit_rate(cache.map(|cache| cache.hit_rate()))
+        })
+        .collect();
+
+    reporter.emit(&records, || {
+        println!(
+            "=== Confluent Wire Format ({} records, schema id {}) ===",
+            people.len(),
+            id
+        );
+        println!(
+            "Mock registry at http://{}, subjects {}",
+            registry.addr, subjects
+        );
+        println!("Unknown schema ids are rejected: {}", rejection);
+        println!(
+            "Size: {} bytes as datums, {} framed (+{:.1}%, {} bytes per record)",
+            datum_bytes,
+            message_bytes,
+            (message_bytes - datum_bytes) as f64 * 100.0 / datum_bytes.max(1) as f64,
+            HEADER_LEN
+        );
+        for pair in results.chunks(2) {
+            let (plain, framed) = (&pair[0], &pair[1]);
+            let cache = framed.3.unwrap_or_default();
+            println!(
+                "  {:<8} {:.6} seconds plain, {:.6} seconds framed ({:+.1}%)",
+                plain
+                    .0
+                    .rsplit_once('-')
+                    .map_or(plain.0, |(_, direction)| direction),
+                plain.2.mean,
+                framed.2.mean,
+                (framed.2.mean / plain.2.mean - 1.0) * 100.0
+            );
+            println!(
+                "    Schema cache: {} hits, {} registry lookups ({:.3}% hit rate)",
+                cache.hits,
+                cache.misses,
+                cache.hit_rate() * 100.0
+            );
+            print_stats(plain.2, people.len());
+            print_stats(framed.2, people.len());
+        }
+    });
+    Ok(())
+}
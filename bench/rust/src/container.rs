@@ -0,0 +1,45 @@
+// Object container file header
+// The magic bytes, a map<bytes> of metadata (avro.schema, avro.codec and any
+// user entries), then the 16-byte sync marker. apache-avro's Reader only
+// exposes the user entries, so the ops that need the rest decode it here.
+
+use apache_avro::types::Value;
+use apache_avro::Schema;
+use avro_rust_bench_common::Result;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+
+pub(crate) const MAGIC: [u8; 4] = [b'O', b'b', b'j', 1];
+pub(crate) const SYNC_LEN: usize = 16;
+
+/// The schema of the header's metadata map.
+pub(crate) fn metadata_schema() -> Schema {
+    Schema::map(Schema::Bytes)
+}
+
+/// Read the magic bytes and the metadata map, leaving `reader` at the sync
+/// marker.
+pub(crate) fn read_metadata(reader: &mut impl Read) -> Result<HashMap<String, Vec<u8>>> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err("not an Avro object container file".into());
+    }
+    match apache_avro::from_avro_datum(&metadata_schema(), reader, None)? {
+        Value::Map(entries) => entries
+            .into_iter()
+            .map(|(key, value)| match value {
+                Value::Bytes(bytes) => Ok((key, bytes)),
+                other => Err(format!("unexpected metadata value for {}: {:?}", key, other).into()),
+            })
+            .collect(),
+        other => Err(format!("unexpected header metadata: {:?}", other).into()),
+    }
+}
+
+/// Every metadata entry of the container file at `path`.
+pub(crate) fn read_file_metadata(path: &str) -> Result<HashMap<String, Vec<u8>>> {
+    read_metadata(&mut BufReader::new(File::open(path)?))
+        .map_err(|err| format!("{}: {}", path, err).into())
+}
@@ -0,0 +1,220 @@
+// Schema evolution: Person written with a v1 schema and read with a v2 reader
+// schema, exercising the same writer/reader resolution rules as
+// Resolution.resolve_schemas on the OCaml side:
+// - `country` is added with a default
+// - `internal_id` is removed
+// - `age` is promoted int -> long, `score` float -> double
+// - `phone_numbers` is renamed to `phones` via aliases
+
+use crate::backend::ApacheBackend;
+use apache_avro::{from_avro_datum, from_value, to_value, Schema};
+use avro_rust_bench_common::cli::Options;
+use avro_rust_bench_common::harness;
+use avro_rust_bench_common::report::{Reporter, ResultRecord};
+use avro_rust_bench_common::workloads::print_stats;
+use avro_rust_bench_common::{create_person, AvroBackend, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PersonV1 {
+    name: String,
+    age: i32,
+    email: Option<String>,
+    phone_numbers: Vec<String>,
+    score: f32,
+    internal_id: i32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct PersonV2 {
+    name: String,
+    age: i64,
+    email: Option<String>,
+    phones: Vec<String>,
+    score: f64,
+    country: String,
+}
+
+fn create_person_v1(i: i32) -> PersonV1 {
+    let person = create_person(i);
+    PersonV1 {
+        name: person.name,
+        age: person.age,
+        email: person.email,
+        phone_numbers: person.phone_numbers,
+        score: (i % 200) as f32 * 0.5,
+        internal_id: i,
+    }
+}
+
+fn expected_person_v2(i: i32) -> PersonV2 {
+    let person = create_person(i);
+    PersonV2 {
+        name: person.name,
+        age: person.age as i64,
+        email: person.email,
+        phones: person.phone_numbers,
+        score: (i % 200) as f64 * 0.5,
+        country: "unknown".to_string(),
+    }
+}
+
+const SCHEMA_V1: &str = r#"
+    {
+        "type": "record",
+        "name": "Person",
+        "fields": [
+            {"name": "name", "type": "string"},
+            {"name": "age", "type": "int"},
+            {"name": "email", "type": ["null", "string"]},
+            {"name": "phone_numbers", "type": {"type": "array", "items": "string"}},
+            {"name": "score", "type": "float"},
+            {"name": "internal_id", "type": "int"}
+        ]
+    }
+"#;
+
+const SCHEMA_V2: &str = r#"
+    {
+        "type": "record",
+        "name": "Person",
+        "fields": [
+            {"name": "name", "type": "string"},
+            {"name": "age", "type": "long"},
+            {"name": "email", "type": ["null", "string"]},
+            {"name": "phones", "aliases": ["phone_numbers"], "type": {"type": "array", "items": "string"}},
+            {"name": "score", "type": "double"},
+            {"name": "country", "type": "string", "default": "unknown"}
+        ]
+    }
+"#;
+
+// apache-avro 0.20 matches record fields by name only during resolution and
+// ignores reader field aliases. Like Java's Schema.applyAliases, rename writer
+// fields to the reader field that lists them as an alias. Only names change,
+// so the binary layout described by the writer schema is untouched.
+fn apply_field_aliases(writer: &Schema, reader: &Schema) -> Schema {
+    let (Schema::Record(writer_record), Schema::Record(reader_record)) = (writer, reader) else {
+        return writer.clone();
+    };
+
+    let mut aliased = writer_record.clone();
+    for field in &mut aliased.fields {
+        if reader_record.fields.iter().any(|f| f.name == field.name) {
+            continue;
+        }
+        let renamed = reader_record.fields.iter().find(|reader_field| {
+            reader_field
+                .aliases
+                .as_ref()
+                .is_some_and(|aliases| aliases.contains(&field.name))
+        });
+        if let Some(reader_field) = renamed {
+            field.name = reader_field.name.clone();
+        }
+    }
+    aliased.lookup = aliased
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(position, field)| (field.name.clone(), position))
+        .collect();
+
+    Schema::Record(aliased)
+}
+
+fn decode_plain(schema: &Schema, encoded: &[Vec<u8>]) -> Result<Vec<PersonV1>> {
+    encoded
+        .iter()
+        .map(|bytes| {
+            Ok(from_value(&from_avro_datum(
+                schema,
+                &mut &bytes[..],
+                None,
+            )?)?)
+        })
+        .collect()
+}
+
+fn decode_evolved(writer: &Schema, reader: &Schema, encoded: &[Vec<u8>]) -> Result<Vec<PersonV2>> {
+    encoded
+        .iter()
+        .map(|bytes| {
+            Ok(from_value(&from_avro_datum(
+                writer,
+                &mut &bytes[..],
+                Some(reader),
+            )?)?)
+        })
+        .collect()
+}
+
+pub fn benchmark_evolve(options: &Options, reporter: &Reporter) -> Result<()> {
+    let writer_schema = Schema::parse_str(SCHEMA_V1)?;
+    let reader_schema = Schema::parse_str(SCHEMA_V2)?;
+    let aliased_writer_schema = apply_field_aliases(&writer_schema, &reader_schema);
+
+    let mut encoded = Vec::new();
+    for i in 0..options.count {
+        let value = to_value(create_person_v1(i))?;
+        let bytes = apache_avro::to_avro_datum(&writer_schema, value)?;
+        encoded.push(bytes);
+    }
+
+    let total_bytes: usize = encoded.iter().map(|b| b.len()).sum();
+
+    // Verify outside the timed section
+    let resolved = decode_evolved(&aliased_writer_schema, &reader_schema, &encoded)?;
+    for (i, person) in resolved.iter().enumerate() {
+        let expected = expected_person_v2(i as i32);
+        if *person != expected {
+            return Err(format!(
+                "Evolve mismatch at record {}: expected {:?}, got {:?}",
+                i, expected, person
+            )
+            .into());
+        }
+    }
+
+    // Baseline: decode with the writer schema only
+    let plain_stats =
+        harness::measure(&options.harness, || decode_plain(&writer_schema, &encoded))?;
+    // Decode with writer/reader resolution
+    let evolve_stats = harness::measure(&options.harness, || {
+        decode_evolved(&aliased_writer_schema, &reader_schema, &encoded)
+    })?;
+
+    let records = [
+        ("evolve-baseline-decode", &plain_stats),
+        ("evolve", &evolve_stats),
+    ]
+    .map(|(operation, stats)| {
+        ResultRecord::measured(
+            ApacheBackend::IMPLEMENTATION,
+            operation,
+            encoded.len(),
+            total_bytes as u64,
+            stats,
+        )
+    });
+    reporter.emit(&records, || {
+        println!(
+            "Decoded {} records in {:.6} seconds ({:.2} MB/s, {} bytes)",
+            encoded.len(),
+            plain_stats.mean,
+            records[0].mb_per_sec,
+            total_bytes
+        );
+        print_stats(&plain_stats, encoded.len());
+        println!(
+            "Evolved {} records in {:.6} seconds ({:.2} MB/s, {} bytes, {:.2}x plain decode)",
+            encoded.len(),
+            evolve_stats.mean,
+            records[1].mb_per_sec,
+            total_bytes,
+            evolve_stats.mean / plain_stats.mean
+        );
+        print_stats(&evolve_stats, encoded.len());
+    });
+    Ok(())
+}
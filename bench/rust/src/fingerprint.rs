@@ -0,0 +1,205 @@
+// Schema fingerprint fixtures (`fingerprint`)
+// Prints the specification's Parsing Canonical Form of every .avsc file in a
+// directory with its CRC-64-AVRO (Rabin), MD5 and SHA-256 fingerprints as JSON,
+// next to apache-avro's own canonical form and Rabin fingerprint. Given a
+// second fixture, such as the one bench/fingerprint_fixture.exe writes for
+// avro-simple, every field the two both carry is compared per file and
+// disagreements are reported. apache-avro 0.20's canonical form keeps logical
+// type attributes, so it gets fields of its own; where it differs from the
+// specification's form a note goes to stderr.
+
+use apache_avro::rabin::Rabin;
+use apache_avro::Schema;
+use avro_rust_bench_common::cli::Options;
+use avro_rust_bench_common::fingerprint::{crc64_avro, parsing_canonical_form};
+use avro_rust_bench_common::Result;
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::fs;
+
+/// One schema file. `canonical_form` and the fingerprints after it are those
+/// of the specification's form; the rest are optional so a fixture that only
+/// carries some of them can still be compared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FixtureEntry {
+    file: String,
+    canonical_form: String,
+    /// CRC-64-AVRO as 16 hex digits, most significant first
+    rabin_fingerprint: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    md5: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sha256: Option<String>,
+    /// `Schema::canonical_form`, which may keep non-canonical attributes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    apache_canonical_form: Option<String>,
+    /// `fingerprint::<Rabin>` of `apache_canonical_form`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    apache_rabin_fingerprint: Option<String>,
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// The specification's canonical form and its fingerprints, with apache-avro's
+// form and Rabin fingerprint alongside
+fn fixture_entry(file: String, json: &str) -> Result<FixtureEntry> {
+    let schema = Schema::parse_str(json).map_err(|err| format!("{}: {}", file, err))?;
+    let apache_form = schema.canonical_form();
+
+    // apache-avro's Rabin digest yields the number as little-endian bytes;
+    // the common crate's CRC must agree on the same form
+    let rabin = schema.fingerprint::<Rabin>().bytes;
+    let apache_crc = u64::from_le_bytes(rabin.as_slice().try_into()?);
+    if apache_crc != crc64_avro(apache_form.as_bytes()) {
+        return Err(format!(
+            "{}: apache-avro's Rabin fingerprint {:016x} disagrees with CRC-64-AVRO {:016x}",
+            file,
+            apache_crc,
+            crc64_avro(apache_form.as_bytes())
+        )
+        .into());
+    }
+
+    let canonical_form =
+        parsing_canonical_form(json).map_err(|err| format!("{}: {}", file, err))?;
+    Ok(FixtureEntry {
+        file,
+        rabin_fingerprint: format!("{:016x}", crc64_avro(canonical_form.as_bytes())),
+        md5: Some(hex(&Md5::digest(&canonical_form))),
+        sha256: Some(hex(&Sha256::digest(&canonical_form))),
+        canonical_form,
+        apache_canonical_form: Some(apache_form),
+        apache_rabin_fingerprint: Some(format!("{:016x}", apache_crc)),
+    })
+}
+
+fn read_schemas(dir: &str) -> Result<Vec<FixtureEntry>> {
+    let mut files: Vec<String> = fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+        .collect::<Result<Vec<_>>>()?;
+    files.retain(|file| file.ends_with(".avsc"));
+    files.sort();
+    if files.is_empty() {
+        return Err(format!("no .avsc files in {}", dir).into());
+    }
+
+    files
+        .into_iter()
+        .map(|file| {
+            let json = fs::read_to_string(format!("{}/{}", dir, file))?;
+            fixture_entry(file, &json)
+        })
+        .collect()
+}
+
+// Fields both fixtures carry that hold different values
+fn differences(ours: &FixtureEntry, theirs: &FixtureEntry) -> Vec<String> {
+    let mut fields = Vec::new();
+    if ours.canonical_form != theirs.canonical_form {
+        fields.push(format!(
+            "canonical form\n      ours:   {}\n      theirs: {}",
+            ours.canonical_form, theirs.canonical_form
+        ));
+    }
+    let optional = [
+        (
+            "rabin_fingerprint",
+            Some(&ours.rabin_fingerprint),
+            Some(&theirs.rabin_fingerprint),
+        ),
+        ("md5", ours.md5.as_ref(), theirs.md5.as_ref()),
+        ("sha256", ours.sha256.as_ref(), theirs.sha256.as_ref()),
+        (
+            "apache_canonical_form",
+            ours.apache_canonical_form.as_ref(),
+            theirs.apache_canonical_form.as_ref(),
+        ),
+        (
+            "apache_rabin_fingerprint",
+            ours.apache_rabin_fingerprint.as_ref(),
+            theirs.apache_rabin_fingerprint.as_ref(),
+        ),
+    ];
+    for (name, ours, theirs) in optional {
+        if let (Some(ours), Some(theirs)) = (ours, theirs) {
+            if !ours.eq_ignore_ascii_case(theirs) {
+                fields.push(format!("{}: ours {}, theirs {}", name, ours, theirs));
+            }
+        }
+    }
+    fields
+}
+
+fn compare(entries: &[FixtureEntry], path: &str) -> Result<()> {
+    let fixture: Vec<FixtureEntry> = serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|err| format!("{}: {}", path, err))?;
+    let mut theirs: BTreeMap<&str, &FixtureEntry> = fixture
+        .iter()
+        .map(|entry| (entry.file.as_str(), entry))
+        .collect();
+
+    let mut disagreements = 0;
+    for ours in entries {
+        match theirs.remove(ours.file.as_str()) {
+            Some(entry) => {
+                let fields = differences(ours, entry);
+                if fields.is_empty() {
+                    println!("  ok        {}", ours.file);
+                } else {
+                    disagreements += 1;
+                    println!("  DIFFERS   {}", ours.file);
+                    for field in fields {
+                        println!("    {}", field);
+                    }
+                }
+            }
+            None => {
+                disagreements += 1;
+                println!("  MISSING   {} (not in {})", ours.file, path);
+            }
+        }
+    }
+    for file in theirs.keys() {
+        disagreements += 1;
+        println!("  EXTRA     {} (only in {})", file, path);
+    }
+
+    if disagreements > 0 {
+        return Err(format!(
+            "{} of {} schemas disagree with {}",
+            disagreements,
+            entries.len() + theirs.len(),
+            path
+        )
+        .into());
+    }
+    println!("All {} schemas agree with {}", entries.len(), path);
+    Ok(())
+}
+
+/// `fingerprint <schema-dir> [fixture.json]`
+pub fn fingerprint(options: &Options) -> Result<()> {
+    let entries = read_schemas(options.required(1, "a directory of .avsc files")?)?;
+    // Reported on stderr so the fixture on stdout stays valid JSON
+    for entry in &entries {
+        match &entry.apache_canonical_form {
+            Some(apache_form) if *apache_form != entry.canonical_form => eprintln!(
+                "note: apache-avro's canonical form of {} differs from the specification's\n  spec:        {}\n  apache-avro: {}",
+                entry.file, entry.canonical_form, apache_form
+            ),
+            _ => {}
+        }
+    }
+
+    match options.positional.get(2) {
+        Some(path) => compare(&entries, path),
+        None => {
+            println!("{}", serde_json::to_string_pretty(&entries)?);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,444 @@
+// Schema-driven workload (`--schema path.avsc`)
+// Parses any schema and generates seeded random Values for it, so encode,
+// decode and container can run against production schemas without code
+// changes. Sizes come from the dataset generator flags: --string-len for
+// strings, bytes and map keys, --items for arrays and maps, --null-ratio for
+// unions with a null branch and --seed.
+
+use crate::backend::ApacheBackend;
+use crate::logical::trim_sign_bytes;
+use apache_avro::schema::{DecimalSchema, FixedSchema, NamesRef, ResolvedSchema};
+use apache_avro::types::Value;
+use apache_avro::{
+    from_avro_datum, to_avro_datum, BigDecimal, Days, Decimal, Duration, Millis, Months, Reader,
+    Schema, Uuid, Writer,
+};
+use avro_rust_bench_common::cli::Options;
+use avro_rust_bench_common::dataset::DatasetConfig;
+use avro_rust_bench_common::harness;
+use avro_rust_bench_common::report::{Reporter, ResultRecord};
+use avro_rust_bench_common::rng::Rng;
+use avro_rust_bench_common::workloads::print_stats;
+use avro_rust_bench_common::{AvroBackend, Result};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+
+// Past this depth arrays and maps are empty and unions take their null branch,
+// or else one that is not a named type, so recursive schemas stay finite
+const MAX_NESTING: usize = 8;
+
+const WORKLOAD: &str = "schema";
+
+/// Random Values for one schema, reproducible from `config.seed`.
+pub struct ValueGenerator<'s> {
+    names: NamesRef<'s>,
+    rng: Rng,
+    config: DatasetConfig,
+}
+
+impl<'s> ValueGenerator<'s> {
+    pub fn new(schema: &'s Schema, config: DatasetConfig) -> Result<ValueGenerator<'s>> {
+        Ok(ValueGenerator {
+            names: ResolvedSchema::try_from(schema)?.get_names().clone(),
+            rng: Rng::new(config.seed),
+            config,
+        })
+    }
+
+    pub fn generate(&mut self, schema: &Schema) -> Result<Value> {
+        self.value(schema, 0)
+    }
+
+    fn value(&mut self, schema: &Schema, depth: usize) -> Result<Value> {
+        let rng = &mut self.rng;
+        let config = &self.config;
+        Ok(match schema {
+            Schema::Null => Value::Null,
+            Schema::Boolean => Value::Boolean(rng.chance(0.5)),
+            Schema::Int => Value::Int(rng.next_u64() as i32),
+            Schema::Long => Value::Long(rng.next_u64() as i64),
+            Schema::Float => Value::Float((rng.next_f64() * 2e6 - 1e6) as f32),
+            Schema::Double => Value::Double(rng.next_f64() * 2e12 - 1e12),
+            Schema::Bytes => Value::Bytes(self.bytes()),
+            Schema::String => Value::String(rng.string(config.string_len)),
+            Schema::Array(array) => {
+                let len = self.collection_len(depth);
+                Value::Array(
+                    (0..len)
+                        .map(|_| self.value(&array.items, depth + 1))
+                        .collect::<Result<_>>()?,
+                )
+            }
+            Schema::Map(map) => {
+                let len = self.collection_len(depth);
+                let mut entries = HashMap::with_capacity(len);
+                for _ in 0..len {
+                    let key = self.rng.string(self.config.string_len);
+                    entries.insert(key, self.value(&map.types, depth + 1)?);
+                }
+                Value::Map(entries)
+            }
+            Schema::Union(union) => {
+                let variants = union.variants();
+                let null = variants.iter().position(|v| *v == Schema::Null);
+                let index = match null {
+                    Some(null) if depth >= MAX_NESTING || rng.chance(config.null_ratio) => null,
+                    _ => {
+                        let mut others: Vec<usize> =
+                            (0..variants.len()).filter(|&i| Some(i) != null).collect();
+                        // Only a named type can lead back to this union
+                        let leaves: Vec<usize> = others
+                            .iter()
+                            .copied()
+                            .filter(|&i| {
+                                !matches!(variants[i], Schema::Record(_) | Schema::Ref { .. })
+                            })
+                            .collect();
+                        if depth >= MAX_NESTING && !leaves.is_empty() {
+                            others = leaves;
+                        }
+                        if others.is_empty() {
+                            null.unwrap_or(0)
+                        } else {
+                            others[(rng.next_u64() % others.len() as u64) as usize]
+                        }
+                    }
+                };
+                let value = self.value(&variants[index], depth + 1)?;
+                Value::Union(index as u32, Box::new(value))
+            }
+            Schema::Record(record) => Value::Record(
+                record
+                    .fields
+                    .iter()
+                    .map(|field| Ok((field.name.clone(), self.value(&field.schema, depth + 1)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            Schema::Enum(schema) => {
+                let index = (rng.next_u64() % schema.symbols.len() as u64) as usize;
+                Value::Enum(index as u32, schema.symbols[index].clone())
+            }
+            Schema::Fixed(fixed) => Value::Fixed(
+                fixed.size,
+                (0..fixed.size).map(|_| rng.next_u64() as u8).collect(),
+            ),
+            Schema::Decimal(decimal) => self.decimal(decimal),
+            Schema::BigDecimal => {
+                let unscaled = rng.next_u64() % 1_000_000_000_000;
+                let text = format!("{}.{:04}", unscaled / 10_000, unscaled % 10_000);
+                Value::BigDecimal(text.parse::<BigDecimal>()?)
+            }
+            Schema::Uuid => Value::Uuid(Uuid::from_u64_pair(rng.next_u64(), rng.next_u64())),
+            Schema::Date => Value::Date((rng.next_u64() % 40_000) as i32),
+            Schema::TimeMillis => Value::TimeMillis((rng.next_u64() % 86_400_000) as i32),
+            Schema::TimeMicros => Value::TimeMicros((rng.next_u64() % 86_400_000_000) as i64),
+            Schema::TimestampMillis => Value::TimestampMillis(self.timestamp_millis()),
+            Schema::TimestampMicros => Value::TimestampMicros(self.timestamp_millis() * 1_000),
+            Schema::TimestampNanos => Value::TimestampNanos(self.timestamp_millis() * 1_000_000),
+            Schema::LocalTimestampMillis => Value::LocalTimestampMillis(self.timestamp_millis()),
+            Schema::LocalTimestampMicros => {
+                Value::LocalTimestampMicros(self.timestamp_millis() * 1_000)
+            }
+            Schema::LocalTimestampNanos => {
+                Value::LocalTimestampNanos(self.timestamp_millis() * 1_000_000)
+            }
+            Schema::Duration => Value::Duration(Duration::new(
+                Months::new((rng.next_u64() % 240) as u32),
+                Days::new((rng.next_u64() % 31) as u32),
+                Millis::new((rng.next_u64() % 86_400_000) as u32),
+            )),
+            Schema::Ref { name } => {
+                let schema = *self
+                    .names
+                    .get(name)
+                    .ok_or_else(|| format!("unresolved schema reference {}", name))?;
+                self.value(schema, depth)?
+            }
+        })
+    }
+
+    fn collection_len(&mut self, depth: usize) -> usize {
+        if depth >= MAX_NESTING {
+            0
+        } else {
+            self.rng.range(self.config.items)
+        }
+    }
+
+    fn bytes(&mut self) -> Vec<u8> {
+        let len = self.rng.range(self.config.string_len);
+        (0..len).map(|_| self.rng.next_u64() as u8).collect()
+    }
+
+    // 2020-09-13 onwards, within about three years
+    fn timestamp_millis(&mut self) -> i64 {
+        1_600_000_000_000 + (self.rng.next_u64() % 100_000_000_000) as i64
+    }
+
+    // An unscaled value within the precision, and within the size when the
+    // decimal is on fixed. A zero-size fixed only holds zero, as no bytes;
+    // apache-avro cannot sign-extend a Decimal to zero bytes, so it is given
+    // as the Fixed it is written as.
+    fn decimal(&mut self, decimal: &DecimalSchema) -> Value {
+        let mut bound = 10u64.saturating_pow(decimal.precision.min(18) as u32);
+        let size = match decimal.inner.as_ref() {
+            Schema::Fixed(FixedSchema { size, .. }) => Some(*size),
+            _ => None,
+        };
+        if size == Some(0) {
+            return Value::Fixed(0, Vec::new());
+        }
+        if let Some(size) = size.filter(|&size| size < 8) {
+            bound = bound.min(1 << (size * 8 - 1));
+        }
+        let magnitude = (self.rng.next_u64() % bound) as i64;
+        let unscaled = if self.rng.chance(0.5) {
+            -magnitude
+        } else {
+            magnitude
+        };
+        Value::Decimal(Decimal::from(trim_sign_bytes(&unscaled.to_be_bytes())))
+    }
+}
+
+// Whether `decoded` is `expected` read back. A zero-size decimal is generated
+// as an empty Fixed but decodes as a Decimal.
+fn round_trips(expected: &Value, decoded: &Value) -> bool {
+    match (expected, decoded) {
+        (Value::Fixed(0, _), Value::Decimal(decimal)) => *decimal == Decimal::from(Vec::new()),
+        (Value::Union(i, expected), Value::Union(j, decoded)) => {
+            i == j && round_trips(expected, decoded)
+        }
+        (Value::Record(expected), Value::Record(decoded)) => {
+            expected.len() == decoded.len()
+                && expected
+                    .iter()
+                    .zip(decoded)
+                    .all(|((a, x), (b, y))| a == b && round_trips(x, y))
+        }
+        (Value::Array(expected), Value::Array(decoded)) => {
+            expected.len() == decoded.len()
+                && expected.iter().zip(decoded).all(|(x, y)| round_trips(x, y))
+        }
+        (Value::Map(expected), Value::Map(decoded)) => {
+            expected.len() == decoded.len()
+                && expected
+                    .iter()
+                    .all(|(key, x)| decoded.get(key).is_some_and(|y| round_trips(x, y)))
+        }
+        _ => expected == decoded,
+    }
+}
+
+fn schema_name(schema: &Schema) -> String {
+    match schema {
+        Schema::Record(record) => record.name.fullname(None),
+        Schema::Enum(schema) => schema.name.fullname(None),
+        Schema::Fixed(fixed) => fixed.name.fullname(None),
+        other => format!("{:?}", apache_avro::schema::SchemaKind::from(other)),
+    }
+}
+
+pub fn benchmark_schema(path: &str, options: &Options, reporter: &Reporter) -> Result<()> {
+    let operation = options.operation.as_str();
+    if !["encode", "decode", "container"].contains(&operation) {
+        return Err(format!("{} does not support --schema", operation).into());
+    }
+    let schema = Schema::parse_str(&std::fs::read_to_string(path)?)
+        .map_err(|err| format!("{}: {}", path, err))?;
+    let mut generator = ValueGenerator::new(&schema, options.dataset_config)?;
+    let values = (0..options.count)
+        .map(|_| generator.generate(&schema))
+        .collect::<Result<Vec<_>>>()?;
+
+    if reporter.is_text() {
+        println!(
+            "Schema {} from {}: {} generated records (seed {})",
+            schema_name(&schema),
+            path,
+            values.len(),
+            options.dataset_config.seed
+        );
+    }
+    match operation {
+        "encode" => benchmark_encode(&schema, &values, options, reporter),
+        "decode" => benchmark_decode(&schema, &values, options, reporter),
+        _ => benchmark_container(&schema, &values, options, reporter),
+    }
+}
+
+fn benchmark_encode(
+    schema: &Schema,
+    values: &[Value],
+    options: &Options,
+    reporter: &Reporter,
+) -> Result<()> {
+    // to_avro_datum takes its Value by value; the copies are made untimed
+    let mut total_bytes = 0;
+    let stats = harness::measure_with_setup(
+        &options.harness,
+        || Ok(values.to_vec()),
+        |values| {
+            total_bytes = 0;
+            for value in values {
+                total_bytes += to_avro_datum(schema, value)?.len();
+            }
+            Ok(())
+        },
+    )?;
+
+    let record = ResultRecord::measured(
+        ApacheBackend::IMPLEMENTATION,
+        "encode",
+        values.len(),
+        total_bytes as u64,
+        &stats,
+    )
+    .with_workload(WORKLOAD);
+    reporter.emit(std::slice::from_ref(&record), || {
+        println!(
+            "Encoded {} records in {:.6} seconds ({:.2} MB/s, {} bytes)",
+            values.len(),
+            record.elapsed_secs,
+            record.mb_per_sec,
+            total_bytes
+        );
+        print_stats(&stats, values.len());
+    });
+    Ok(())
+}
+
+fn benchmark_decode(
+    schema: &Schema,
+    values: &[Value],
+    options: &Options,
+    reporter: &Reporter,
+) -> Result<()> {
+    let encoded = values
+        .iter()
+        .map(|value| Ok(to_avro_datum(schema, value.clone())?))
+        .collect::<Result<Vec<_>>>()?;
+    let total_bytes: usize = encoded.iter().map(|b| b.len()).sum();
+
+    // Round-trip check, outside the timed section
+    for (i, (value, bytes)) in values.iter().zip(&encoded).enumerate() {
+        let decoded = from_avro_datum(schema, &mut &bytes[..], None)?;
+        if !round_trips(value, &decoded) {
+            return Err(format!(
+                "Round-trip mismatch at record {}: expected {:?}, got {:?}",
+                i, value, decoded
+            )
+            .into());
+        }
+    }
+
+    let stats = harness::measure(&options.harness, || {
+        for bytes in &encoded {
+            let _value = from_avro_datum(schema, &mut &bytes[..], None)?;
+        }
+        Ok(())
+    })?;
+
+    let record = ResultRecord::measured(
+        ApacheBackend::IMPLEMENTATION,
+        "decode",
+        encoded.len(),
+        total_bytes as u64,
+        &stats,
+    )
+    .with_workload(WORKLOAD);
+    reporter.emit(std::slice::from_ref(&record), || {
+        println!(
+            "Decoded {} records in {:.6} seconds ({:.2} MB/s, {} bytes)",
+            encoded.len(),
+            record.elapsed_secs,
+            record.mb_per_sec,
+            total_bytes
+        );
+        print_stats(&stats, encoded.len());
+    });
+    Ok(())
+}
+
+fn benchmark_container(
+    schema: &Schema,
+    values: &[Value],
+    options: &Options,
+    reporter: &Reporter,
+) -> Result<()> {
+    let compression = options.compression.as_str();
+    let codec = ApacheBackend::codec(compression)?;
+    let path = std::env::temp_dir().join(format!(
+        "bench_{}_schema_{}.avro",
+        ApacheBackend::IMPLEMENTATION,
+        compression
+    ));
+
+    let write_stats = harness::measure(&options.harness, || {
+        let mut writer = Writer::builder()
+            .schema(schema)
+            .writer(BufWriter::new(File::create(&path)?))
+            .codec(codec)
+            .maybe_block_size(options.block_size)
+            .build();
+        for value in values {
+            writer.append_value_ref(value)?;
+        }
+        writer.flush()?;
+        Ok(())
+    })?;
+    let read_stats = harness::measure(&options.harness, || {
+        let mut count = 0;
+        for value in Reader::new(BufReader::new(File::open(&path)?))? {
+            value?;
+            count += 1;
+        }
+        if count != values.len() {
+            return Err(format!("read {} records back, expected {}", count, values.len()).into());
+        }
+        Ok(())
+    })?;
+    let file_size = std::fs::metadata(&path)?.len();
+    std::fs::remove_file(&path)?;
+
+    let records = [
+        ResultRecord::measured(
+            ApacheBackend::IMPLEMENTATION,
+            "container-write",
+            values.len(),
+            file_size,
+            &write_stats,
+        ),
+        ResultRecord::measured(
+            ApacheBackend::IMPLEMENTATION,
+            "container-read",
+            values.len(),
+            file_size,
+            &read_stats,
+        ),
+    ]
+    .map(|record| {
+        record
+            .with_codec(compression)
+            .with_block_size(options.block_size)
+            .with_workload(WORKLOAD)
+    });
+    reporter.emit(&records, || {
+        println!(
+            "Container[{}]: Wrote {} records in {:.6} seconds, Read in {:.6} seconds ({} bytes)",
+            compression,
+            values.len(),
+            write_stats.mean,
+            read_stats.mean,
+            file_size
+        );
+        println!(
+            "  Write: {:.2} MB/s, Read: {:.2} MB/s",
+            records[0].mb_per_sec, records[1].mb_per_sec
+        );
+        print_stats(&write_stats, values.len());
+        print_stats(&read_stats, values.len());
+    });
+    Ok(())
+}
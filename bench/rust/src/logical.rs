@@ -0,0 +1,415 @@
+// Logical types workload for apache-avro
+// apache-avro 0.20 cannot carry decimal or duration through serde: to_value
+// produces Bytes where the encoder wants Decimal or a 12-byte Fixed, and
+// from_value rejects Duration. Applications using these types build Value
+// directly, so this workload converts LogicalRecord to and from Value inside
+// the timed section, for datums and container files alike.
+
+use crate::backend::ApacheBackend;
+use apache_avro::types::Value;
+use apache_avro::{Codec, Days, Decimal, Duration, Millis, Months, Reader, Schema, Writer};
+use avro_rust_bench_common::cli::Options;
+use avro_rust_bench_common::harness;
+use avro_rust_bench_common::logical::{
+    self, create_logical_records, LogicalRecord, BALANCE_SCALE, PRICE_SCALE,
+};
+use avro_rust_bench_common::report::{Reporter, ResultRecord};
+use avro_rust_bench_common::workloads::print_stats;
+use avro_rust_bench_common::{AvroBackend, Result, LOGICAL_SCHEMA};
+use chrono::DateTime;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+pub fn benchmark_logical(options: &Options, reporter: &Reporter) -> Result<()> {
+    let schema = Schema::parse_str(LOGICAL_SCHEMA)?;
+    let records = create_logical_records(options.count);
+
+    match options.operation.as_str() {
+        "encode" => benchmark_encode(&schema, &records, options, reporter),
+        "decode" => benchmark_decode(&schema, &records, options, reporter),
+        "container" => benchmark_container(&schema, &records, options, reporter),
+        op => Err(format!("{} does not support --workload logical", op).into()),
+    }
+}
+
+fn encode(schema: &Schema, record: &LogicalRecord) -> Result<Vec<u8>> {
+    Ok(apache_avro::to_avro_datum(schema, to_avro_value(record))?)
+}
+
+fn decode(schema: &Schema, bytes: &[u8]) -> Result<LogicalRecord> {
+    from_avro_value(apache_avro::from_avro_datum(schema, &mut &bytes[..], None)?)
+}
+
+fn benchmark_encode(
+    schema: &Schema,
+    records: &[LogicalRecord],
+    options: &Options,
+    reporter: &Reporter,
+) -> Result<()> {
+    let mut total_bytes = 0;
+
+    let stats = harness::measure(&options.harness, || {
+        total_bytes = 0;
+        for record in records {
+            total_bytes += encode(schema, record)?.len();
+        }
+        Ok(())
+    })?;
+
+    let result = ResultRecord::measured(
+        ApacheBackend::IMPLEMENTATION,
+        "encode",
+        records.len(),
+        total_bytes as u64,
+        &stats,
+    )
+    .with_workload(options.workload.name());
+    reporter.emit(std::slice::from_ref(&result), || {
+        println!(
+            "Encoded {} records in {:.6} seconds ({:.2} MB/s, {} bytes)",
+            records.len(),
+            result.elapsed_secs,
+            result.mb_per_sec,
+            total_bytes
+        );
+        print_stats(&stats, records.len());
+    });
+    Ok(())
+}
+
+fn benchmark_decode(
+    schema: &Schema,
+    records: &[LogicalRecord],
+    options: &Options,
+    reporter: &Reporter,
+) -> Result<()> {
+    let encoded = records
+        .iter()
+        .map(|record| encode(schema, record))
+        .collect::<Result<Vec<_>>>()?;
+    let total_bytes: usize = encoded.iter().map(|b| b.len()).sum();
+
+    // Round-trip check, outside the timed section
+    for (i, (record, bytes)) in records.iter().zip(&encoded).enumerate() {
+        let decoded = decode(schema, bytes)?;
+        if decoded != *record {
+            return Err(format!(
+                "Round-trip mismatch at record {}: expected {:?}, got {:?}",
+                i, record, decoded
+            )
+            .into());
+        }
+    }
+
+    let stats = harness::measure(&options.harness, || {
+        for bytes in &encoded {
+            let _record = decode(schema, bytes)?;
+        }
+        Ok(())
+    })?;
+
+    let result = ResultRecord::measured(
+        ApacheBackend::IMPLEMENTATION,
+        "decode",
+        encoded.len(),
+        total_bytes as u64,
+        &stats,
+    )
+    .with_workload(options.workload.name());
+    reporter.emit(std::slice::from_ref(&result), || {
+        println!(
+            "Decoded {} records in {:.6} seconds ({:.2} MB/s, {} bytes)",
+            encoded.len(),
+            result.elapsed_secs,
+            result.mb_per_sec,
+            total_bytes
+        );
+        print_stats(&stats, encoded.len());
+    });
+    Ok(())
+}
+
+fn write_container(
+    schema: &Schema,
+    records: &[LogicalRecord],
+    codec: Codec,
+    block_size: Option<usize>,
+    path: &Path,
+) -> Result<()> {
+    let mut writer = Writer::builder()
+        .schema(schema)
+        .writer(BufWriter::new(File::create(path)?))
+        .codec(codec)
+        .maybe_block_size(block_size)
+        .build();
+    for record in records {
+        writer.append(to_avro_value(record))?;
+    }
+    writer.into_inner()?.flush()?;
+    Ok(())
+}
+
+fn read_container(path: &Path) -> Result<Vec<LogicalRecord>> {
+    Reader::new(BufReader::new(File::open(path)?))?
+        .map(|value| from_avro_value(value?))
+        .collect()
+}
+
+fn benchmark_container(
+    schema: &Schema,
+    records: &[LogicalRecord],
+    options: &Options,
+    reporter: &Reporter,
+) -> Result<()> {
+    let compression = options.compression.as_str();
+    let codec = ApacheBackend::codec(compression)?;
+    let path = std::env::temp_dir().join(format!(
+        "bench_{}_{}.avro",
+        ApacheBackend::IMPLEMENTATION,
+        compression
+    ));
+
+    let write_stats = harness::measure(&options.harness, || {
+        write_container(schema, records, codec, options.block_size, &path)
+    })?;
+
+    // Round-trip check, outside the timed section
+    let decoded = read_container(&path)?;
+    if let Some(i) = (0..records.len()).find(|&i| decoded.get(i) != Some(&records[i])) {
+        return Err(format!(
+            "Container round-trip mismatch at record {} ({} of {} read back)",
+            i,
+            decoded.len(),
+            records.len()
+        )
+        .into());
+    }
+    drop(decoded);
+
+    let read_stats = harness::measure(&options.harness, || {
+        let mut count_read = 0;
+        for value in Reader::new(BufReader::new(File::open(&path)?))? {
+            let _record = from_avro_value(value?)?;
+            count_read += 1;
+        }
+        if count_read != records.len() {
+            return Err(format!(
+                "read {} records back, expected {}",
+                count_read,
+                records.len()
+            )
+            .into());
+        }
+        Ok(())
+    })?;
+
+    let file_size = std::fs::metadata(&path)?.len();
+    std::fs::remove_file(&path)?;
+
+    let results = [
+        ("container-write", &write_stats),
+        ("container-read", &read_stats),
+    ]
+    .map(|(operation, stats)| {
+        ResultRecord::measured(
+            ApacheBackend::IMPLEMENTATION,
+            operation,
+            records.len(),
+            file_size,
+            stats,
+        )
+        .with_codec(compression)
+        .with_block_size(options.block_size)
+        .with_workload(options.workload.name())
+    });
+    reporter.emit(&results, || {
+        println!(
+            "Container[{}]: Wrote {} records in {:.6} seconds, Read in {:.6} seconds ({} bytes)",
+            compression,
+            records.len(),
+            write_stats.mean,
+            read_stats.mean,
+            file_size
+        );
+        println!(
+            "  Write: {:.2} MB/s, Read: {:.2} MB/s",
+            results[0].mb_per_sec, results[1].mb_per_sec
+        );
+        if write_stats.iterations > 1 {
+            println!("  write: {}", write_stats.summary());
+            println!("  read:  {}", read_stats.summary());
+        }
+        if let (Some(write), Some(read)) = (&write_stats.alloc, &read_stats.alloc) {
+            println!("  write {}", write.summary(records.len()));
+            println!("  read  {}", read.summary(records.len()));
+        }
+    });
+    Ok(())
+}
+
+fn to_avro_value(record: &LogicalRecord) -> Value {
+    let interval = record.interval;
+    Value::Record(vec![
+        (
+            "date".to_string(),
+            Value::Date(logical::date_to_days(record.date)),
+        ),
+        (
+            "time_millis".to_string(),
+            Value::TimeMillis(logical::time_to_millis(record.time_millis)),
+        ),
+        (
+            "time_micros".to_string(),
+            Value::TimeMicros(logical::time_to_micros(record.time_micros)),
+        ),
+        (
+            "timestamp_millis".to_string(),
+            Value::TimestampMillis(record.timestamp_millis.timestamp_millis()),
+        ),
+        (
+            "timestamp_micros".to_string(),
+            Value::TimestampMicros(record.timestamp_micros.timestamp_micros()),
+        ),
+        (
+            "local_timestamp_millis".to_string(),
+            Value::LocalTimestampMillis(record.local_timestamp_millis.and_utc().timestamp_millis()),
+        ),
+        (
+            "local_timestamp_micros".to_string(),
+            Value::LocalTimestampMicros(record.local_timestamp_micros.and_utc().timestamp_micros()),
+        ),
+        (
+            "price".to_string(),
+            Value::Decimal(to_avro_decimal(record.price, PRICE_SCALE)),
+        ),
+        (
+            "balance".to_string(),
+            Value::Decimal(to_avro_decimal(record.balance, BALANCE_SCALE)),
+        ),
+        ("id".to_string(), Value::Uuid(record.id)),
+        (
+            "interval".to_string(),
+            Value::Duration(Duration::new(
+                Months::new(interval.months),
+                Days::new(interval.days),
+                Millis::new(interval.milliseconds),
+            )),
+        ),
+    ])
+}
+
+fn from_avro_value(value: Value) -> Result<LogicalRecord> {
+    let Value::Record(fields) = value else {
+        return Err(format!("expected a record, got {:?}", value).into());
+    };
+    let mut fields = fields.into_iter().map(|(_, value)| value);
+    let mut next = || fields.next().ok_or("LogicalRecord is missing fields");
+    let out_of_range = |what: &str| format!("{} out of range", what);
+
+    Ok(LogicalRecord {
+        date: match next()? {
+            Value::Date(days) => logical::days_to_date(days).ok_or(out_of_range("date"))?,
+            other => return Err(unexpected("date", other)),
+        },
+        time_millis: match next()? {
+            Value::TimeMillis(millis) => {
+                logical::millis_to_time(millis).ok_or(out_of_range("time-millis"))?
+            }
+            other => return Err(unexpected("time_millis", other)),
+        },
+        time_micros: match next()? {
+            Value::TimeMicros(micros) => {
+                logical::micros_to_time(micros).ok_or(out_of_range("time-micros"))?
+            }
+            other => return Err(unexpected("time_micros", other)),
+        },
+        timestamp_millis: match next()? {
+            Value::TimestampMillis(millis) => {
+                DateTime::from_timestamp_millis(millis).ok_or(out_of_range("timestamp-millis"))?
+            }
+            other => return Err(unexpected("timestamp_millis", other)),
+        },
+        timestamp_micros: match next()? {
+            Value::TimestampMicros(micros) => {
+                DateTime::from_timestamp_micros(micros).ok_or(out_of_range("timestamp-micros"))?
+            }
+            other => return Err(unexpected("timestamp_micros", other)),
+        },
+        local_timestamp_millis: match next()? {
+            Value::LocalTimestampMillis(millis) => {
+                logical::millis_to_local(millis).ok_or(out_of_range("local-timestamp-millis"))?
+            }
+            other => return Err(unexpected("local_timestamp_millis", other)),
+        },
+        local_timestamp_micros: match next()? {
+            Value::LocalTimestampMicros(micros) => {
+                logical::micros_to_local(micros).ok_or(out_of_range("local-timestamp-micros"))?
+            }
+            other => return Err(unexpected("local_timestamp_micros", other)),
+        },
+        price: match next()? {
+            Value::Decimal(decimal) => from_avro_decimal(&decimal, PRICE_SCALE)?,
+            other => return Err(unexpected("price", other)),
+        },
+        balance: match next()? {
+            Value::Decimal(decimal) => from_avro_decimal(&decimal, BALANCE_SCALE)?,
+            other => return Err(unexpected("balance", other)),
+        },
+        id: match next()? {
+            Value::Uuid(uuid) => uuid,
+            other => return Err(unexpected("id", other)),
+        },
+        interval: match next()? {
+            Value::Duration(duration) => logical::Duration {
+                months: duration.months().into(),
+                days: duration.days().into(),
+                milliseconds: duration.millis().into(),
+            },
+            other => return Err(unexpected("interval", other)),
+        },
+    })
+}
+
+fn unexpected(field: &str, value: Value) -> avro_rust_bench_common::Error {
+    format!("unexpected value for {}: {:?}", field, value).into()
+}
+
+// Avro decimals are the two's-complement big-endian unscaled value; the scale
+// lives in the schema
+fn to_avro_decimal(decimal: rust_decimal::Decimal, scale: u32) -> Decimal {
+    let mut decimal = decimal;
+    decimal.rescale(scale);
+    Decimal::from(trim_sign_bytes(&decimal.mantissa().to_be_bytes()))
+}
+
+/// A two's-complement big-endian number without the leading bytes that only
+/// repeat the sign, keeping at least one byte.
+pub(crate) fn trim_sign_bytes(bytes: &[u8]) -> &[u8] {
+    let start = (0..bytes.len().saturating_sub(1))
+        .find(|&i| {
+            let redundant = (bytes[i] == 0x00 && bytes[i + 1] & 0x80 == 0)
+                || (bytes[i] == 0xff && bytes[i + 1] & 0x80 != 0);
+            !redundant
+        })
+        .unwrap_or(bytes.len().saturating_sub(1));
+    &bytes[start..]
+}
+
+fn from_avro_decimal(decimal: &Decimal, scale: u32) -> Result<rust_decimal::Decimal> {
+    let bytes = Vec::<u8>::try_from(decimal)?;
+    if bytes.len() > 16 {
+        return Err(format!("decimal of {} bytes does not fit in 128 bits", bytes.len()).into());
+    }
+    let fill = if bytes.first().is_some_and(|b| b & 0x80 != 0) {
+        0xff
+    } else {
+        0x00
+    };
+    let mut buf = [fill; 16];
+    buf[16 - bytes.len()..].copy_from_slice(&bytes);
+    Ok(rust_decimal::Decimal::try_from_i128_with_scale(
+        i128::from_be_bytes(buf),
+        scale,
+    )?)
+}
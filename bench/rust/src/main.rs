@@ -2,173 +2,227 @@
 // This uses the standard Value-based approach which has inherent overhead
 // See PERFORMANCE_ANALYSIS.md for details
 
-use apache_avro::{from_value, to_value, Codec, Reader, Schema, Writer};
-use serde::{Deserialize, Serialize};
-use std::env;
-use std::fs::File;
-use std::time::Instant;
+mod append;
+mod backend;
+mod block_sweep;
+mod compression;
+mod confluent;
+mod container;
+mod evolve;
+mod fingerprint;
+mod generic;
+mod logical;
+mod metadata;
+mod modes;
+mod parallel;
+mod phases;
+mod pipelined;
+mod single_object;
+mod streaming;
+mod verify;
+
+use avro_rust_bench_common::alloc::CountingAllocator;
+use avro_rust_bench_common::cli::{self, Options};
+use avro_rust_bench_common::report::Reporter;
+use avro_rust_bench_common::tree::{self, create_trees};
+use avro_rust_bench_common::workloads::Workload;
+use avro_rust_bench_common::{dataset, datums, workloads, AvroBackend, PERSON_SCHEMA, TREE_SCHEMA};
+use backend::ApacheBackend;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const USAGE: &str = "[encode|decode|container|evolve|compression|streaming|single-object|confluent] [count] [compression] \
+                     [--workload person|logical|tree] [--depth N] [--fanout N] [--warmup N] [--iterations N] [--measure-alloc]
+                     [--format text|json|csv] [--block-size N[K|M]]
+       avro-rust-bench encode [count] --phases
+       avro-rust-bench [encode|decode] [count] --mode serde|value|single-object|writer|all
+       avro-rust-bench [encode|decode|container|compression] --dataset <people.jsonl>
+       avro-rust-bench generate-dataset [count] <output> [--seed N] [--string-len MIN..MAX]
+                       [--null-ratio F] [--phones MIN..MAX]
+       avro-rust-bench [encode|decode|container] [count] [compression] --schema <schema.avsc>
+                       [--seed N] [--string-len MIN..MAX] [--items MIN..MAX] [--null-ratio F]
+       avro-rust-bench block-sweep [count]
+       avro-rust-bench parallel-read [count] [compression] [--threads N] [--input <file.avro>]
+       avro-rust-bench pipelined-write [count] [compression] [--threads N] [--output <file.avro>]
+       avro-rust-bench append [count] [compression] [--input <file.avro>] [--output <file.avro>]
+       avro-rust-bench metadata [count] [compression] [--input <file.avro>] [--output <file.avro>]
+       avro-rust-bench verify <container.avro>
+       avro-rust-bench fingerprint <schema-dir> [fixture.json]
+       avro-rust-bench depth-stress --workload tree
+       avro-rust-bench emit-datums [count] <output>
+       avro-rust-bench compare-datums <a> <b>";
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Person {
-    name: String,
-    age: i32,
-    email: Option<String>,
-    phone_numbers: Vec<String>,
-}
-
-fn create_person(i: i32) -> Person {
-    Person {
-        name: format!("Person_{}", i),
-        age: 20 + (i % 60),
-        email: if i % 3 == 0 {
-            Some(format!("person{}@example.com", i))
-        } else {
-            None
-        },
-        phone_numbers: (0..(1 + i % 3))
-            .map(|j| format!("+1-555-{:04}", i * 10 + j))
-            .collect(),
+fn main() {
+    let mut options = Options::from_env(&["phases"]).unwrap_or_else(|err| {
+        cli::usage_error("avro-rust-bench", USAGE, &err);
+    });
+    let phases = options.take_switch("phases");
+    if phases && (options.operation != "encode" || options.workload != Workload::Person) {
+        cli::usage_error(
+            &options.program,
+            USAGE,
+            "--phases only applies to encode with the person workload",
+        );
     }
-}
-
-fn get_schema() -> Schema {
-    let schema_str = r#"
+    let modes = match options.take("mode").map(|value| modes::parse_modes(&value)) {
+        Some(Ok(modes)) => Some(modes),
+        Some(Err(err)) => cli::usage_error(&options.program, USAGE, &err),
+        None => None,
+    };
+    if modes.is_some()
+        && (phases
+            || !["encode", "decode"].contains(&options.operation.as_str())
+            || options.workload != Workload::Person)
     {
-        "type": "record",
-        "name": "Person",
-        "fields": [
-            {"name": "name", "type": "string"},
-            {"name": "age", "type": "int"},
-            {"name": "email", "type": ["null", "string"]},
-            {"name": "phone_numbers", "type": {"type": "array", "items": "string"}}
-        ]
+        cli::usage_error(
+            &options.program,
+            USAGE,
+            "--mode only applies to encode and decode with the person workload",
+        );
     }
-    "#;
-    Schema::parse_str(schema_str).unwrap()
-}
-
-fn benchmark_encode(count: i32) {
-    let schema = get_schema();
-    let people: Vec<Person> = (0..count).map(create_person).collect();
-
-    let start = Instant::now();
-    let mut total_bytes = 0;
-
-    // NOTE: apache-avro crate has inherent performance limitations:
-    // 1. to_value() creates intermediate Value representation (serde overhead)
-    // 2. to_avro_datum() then encodes Value to bytes
-    // This two-step process is ~10-20x slower than direct encoding
-    // Alternative crates like serde_avro_fast claim 10-20x speedup by avoiding Value
-
-    for person in &people {
-        // Convert to Value, then encode (matches apache-avro idiom)
-        let value = to_value(person).unwrap();
-        let bytes = apache_avro::to_avro_datum(&schema, value).unwrap();
-        total_bytes += bytes.len();
+    let schema = options.take("schema");
+    if schema.is_some() && (phases || modes.is_some()) {
+        cli::usage_error(
+            &options.program,
+            USAGE,
+            "--schema cannot be combined with --phases or --mode",
+        );
     }
-
-    let elapsed = start.elapsed().as_secs_f64();
-    let mb_per_sec = (total_bytes as f64 / elapsed) / 1_000_000.0;
-
-    println!(
-        "Encoded {} records in {:.6} seconds ({:.2} MB/s, {} bytes)",
-        count, elapsed, mb_per_sec, total_bytes
-    );
-}
-
-fn benchmark_decode(count: i32) {
-    let schema = get_schema();
-    let people: Vec<Person> = (0..count).map(create_person).collect();
-
-    // Encode first
-    let mut encoded = Vec::new();
-    for person in &people {
-        let value = to_value(person).unwrap();
-        let bytes = apache_avro::to_avro_datum(&schema, value).unwrap();
-        encoded.push(bytes);
+    let threads = match options.take_parsed::<usize>("threads") {
+        Ok(Some(0)) => cli::usage_error(&options.program, USAGE, "--threads must be at least 1"),
+        Ok(threads) => threads,
+        Err(err) => cli::usage_error(&options.program, USAGE, &err),
+    };
+    let input = options.take("input");
+    let output = options.take("output");
+    let operation = options.operation.as_str();
+    let threaded = ["parallel-read", "pipelined-write"].contains(&operation);
+    let misplaced = if threads.is_some() && !threaded {
+        Some("--threads only applies to parallel-read and pipelined-write")
+    } else if options.block_size.is_some()
+        && (operation == "block-sweep" || (input.is_some() && operation != "append"))
+    {
+        Some("--block-size does not apply to block-sweep or to reading an --input file")
+    } else if input.is_some() && !["parallel-read", "metadata", "append"].contains(&operation) {
+        Some("--input only applies to parallel-read, metadata and append")
+    } else if output.is_some() && !["pipelined-write", "metadata", "append"].contains(&operation) {
+        Some("--output only applies to pipelined-write, metadata and append")
+    } else if threaded && input.is_none() && options.workload != Workload::Person {
+        Some("parallel-read and pipelined-write use the person workload (parallel-read --input reads any container)")
+    } else if ["metadata", "append"].contains(&operation) && options.workload != Workload::Person {
+        Some("metadata and append use the person workload")
+    } else if threaded && (phases || modes.is_some() || schema.is_some()) {
+        Some("parallel-read and pipelined-write cannot be combined with --phases, --mode or --schema")
+    } else {
+        None
+    };
+    if let Some(err) = misplaced {
+        cli::usage_error(&options.program, USAGE, err);
     }
-
-    let total_bytes: usize = encoded.iter().map(|b| b.len()).sum();
-
-    // Benchmark decode
-    let start = Instant::now();
-    for bytes in &encoded {
-        let value = apache_avro::from_avro_datum(&schema, &mut &bytes[..], None).unwrap();
-        let _person: Person = from_value(&value).unwrap();
+    if let Err(err) = options.finish() {
+        cli::usage_error(&options.program, USAGE, &err);
+    }
+    let reporter = Reporter::new(options.format);
+
+    if phases {
+        cli::exit_on_error(phases::benchmark_encode_phases(&options, &reporter));
+    } else if let Some(schema) = schema {
+        cli::exit_on_error(generic::benchmark_schema(&schema, &options, &reporter));
+    } else if threaded {
+        let threads = threads.unwrap_or_else(|| {
+            std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+        });
+        cli::exit_on_error(match options.operation.as_str() {
+            "parallel-read" => {
+                parallel::benchmark_parallel_read(threads, input.as_deref(), &options, &reporter)
+            }
+            _ => pipelined::benchmark_pipelined_write(
+                threads,
+                output.as_deref(),
+                &options,
+                &reporter,
+            ),
+        });
+    } else if options.operation == "metadata" {
+        cli::exit_on_error(metadata::metadata(
+            input.as_deref(),
+            output.as_deref(),
+            &options,
+        ));
+    } else if options.operation == "append" {
+        cli::exit_on_error(append::benchmark_append(
+            input.as_deref(),
+            output.as_deref(),
+            &options,
+            &reporter,
+        ));
+    } else if let Some(modes) = modes {
+        cli::exit_on_error(modes::benchmark_modes(&modes, &options, &reporter));
+    } else {
+        cli::exit_on_error(run(&options, &reporter));
     }
-
-    let elapsed = start.elapsed().as_secs_f64();
-    let mb_per_sec = (total_bytes as f64 / elapsed) / 1_000_000.0;
-
-    println!(
-        "Decoded {} records in {:.6} seconds ({:.2} MB/s, {} bytes)",
-        count, elapsed, mb_per_sec, total_bytes
-    );
 }
 
-fn benchmark_container(count: i32, compression: &str) {
-    let schema = get_schema();
-    let people: Vec<Person> = (0..count).map(create_person).collect();
-
-    let codec = match compression {
-        "null" => Codec::Null,
-        "deflate" => Codec::Deflate(Default::default()),
-        _ => Codec::Null,
-    };
+fn run(options: &Options, reporter: &Reporter) -> avro_rust_bench_common::Result<()> {
+    match options.workload {
+        Workload::Person => run_person(options, reporter),
+        Workload::Logical => logical::benchmark_logical(options, reporter),
+        Workload::Tree => run_tree(options, reporter),
+    }
+}
 
-    let temp_dir = std::env::temp_dir();
-    let temp_path = temp_dir.join(format!("bench_{}.avro", compression));
+fn run_person(options: &Options, reporter: &Reporter) -> avro_rust_bench_common::Result<()> {
+    let people = || dataset::people(options);
 
-    // Write
-    let start_write = Instant::now();
-    {
-        let mut writer = Writer::with_codec(&schema, File::create(&temp_path).unwrap(), codec);
-        for person in &people {
-            writer.append_ser(person).unwrap();
+    match options.operation.as_str() {
+        "encode" => {
+            let mut backend = ApacheBackend::new(PERSON_SCHEMA)?;
+            workloads::encode(&mut backend, &people()?, options, reporter)
         }
-        writer.flush().unwrap();
-    }
-    let elapsed_write = start_write.elapsed().as_secs_f64();
-
-    // Read
-    let start_read = Instant::now();
-    let reader = Reader::new(File::open(&temp_path).unwrap()).unwrap();
-    let mut _count_read = 0;
-    for _record in reader {
-        _count_read += 1;
+        "decode" => {
+            let mut backend = ApacheBackend::new(PERSON_SCHEMA)?;
+            workloads::decode(&mut backend, &people()?, options, reporter)
+        }
+        "container" => {
+            let mut backend = ApacheBackend::new(PERSON_SCHEMA)?;
+            workloads::container(&mut backend, &people()?, options, reporter)
+        }
+        "generate-dataset" => dataset::generate(options),
+        "evolve" => evolve::benchmark_evolve(options, reporter),
+        "single-object" => single_object::benchmark_single_object(options, reporter),
+        "confluent" => confluent::benchmark_confluent(options, reporter),
+        "streaming" => streaming::benchmark_streaming(options, reporter),
+        "compression" => compression::benchmark_compression(&people()?, options, reporter),
+        "block-sweep" => block_sweep::benchmark_block_sweep(&people()?, options, reporter),
+        "emit-datums" => {
+            let mut backend = ApacheBackend::new(PERSON_SCHEMA)?;
+            datums::emit(&mut backend, &people()?, options.output_path()?)
+        }
+        "compare-datums" => datums::compare(
+            options.required(1, "two datum files")?,
+            options.required(2, "two datum files")?,
+        ),
+        "verify" => verify::verify(options.required(1, "a container file path")?),
+        "fingerprint" => fingerprint::fingerprint(options),
+        op => cli::usage_error(
+            &options.program,
+            USAGE,
+            &format!("Unknown operation {}", op),
+        ),
     }
-    let elapsed_read = start_read.elapsed().as_secs_f64();
-
-    let metadata = std::fs::metadata(&temp_path).unwrap();
-    let file_size = metadata.len();
-
-    std::fs::remove_file(&temp_path).unwrap();
-
-    println!(
-        "Container[{}]: Wrote {} records in {:.6} seconds, Read in {:.6} seconds ({} bytes)",
-        compression, count, elapsed_write, elapsed_read, file_size
-    );
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    let operation = args.get(1).map(String::as_str).unwrap_or("encode");
-    let count: i32 = args
-        .get(2)
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(10000);
-    let compression = args.get(3).map(String::as_str).unwrap_or("null");
-
-    match operation {
-        "encode" => benchmark_encode(count),
-        "decode" => benchmark_decode(count),
-        "container" => benchmark_container(count, compression),
-        _ => {
-            eprintln!(
-                "Usage: {} [encode|decode|container] [count] [compression]",
-                args[0]
-            );
-            std::process::exit(1);
-        }
+fn run_tree(options: &Options, reporter: &Reporter) -> avro_rust_bench_common::Result<()> {
+    let trees = || create_trees(options.count, options.tree);
+    let mut backend = ApacheBackend::new(TREE_SCHEMA)?;
+
+    match options.operation.as_str() {
+        "encode" => workloads::encode(&mut backend, &trees()?, options, reporter),
+        "decode" => workloads::decode(&mut backend, &trees()?, options, reporter),
+        "container" => workloads::container(&mut backend, &trees()?, options, reporter),
+        "depth-stress" => tree::depth_stress(ApacheBackend::IMPLEMENTATION),
+        "depth-probe" => tree::depth_probe(&mut backend, options.required(1, "a depth")?.parse()?),
+        op => Err(format!("{} does not support --workload tree", op).into()),
     }
 }
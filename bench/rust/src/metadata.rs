@@ -0,0 +1,207 @@
+// Container user metadata round trip (`metadata`)
+// avro-simple's Container_writer.create ?metadata puts extra entries in the
+// file header, and our pipeline stores lineage there. This writes the same
+// entries with Writer::add_user_metadata, reads them back through
+// Reader::user_metadata and compares every byte. Keys under the reserved
+// `avro.` prefix must be refused by the writer, and a file whose header carries
+// one besides avro.schema and avro.codec is reported: apache-avro's Reader
+// drops such keys silently, so the raw header is decoded as well.
+
+use crate::backend::ApacheBackend;
+use crate::container::read_file_metadata;
+use crate::verify::describe_bytes;
+use apache_avro::{Reader, Schema, Writer};
+use avro_rust_bench_common::cli::Options;
+use avro_rust_bench_common::{dataset, AvroBackend, Result, PERSON_SCHEMA};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+
+const RESERVED_PREFIX: &str = "avro.";
+
+// The only reserved keys a writer may set
+const SPEC_KEYS: [&str; 2] = ["avro.schema", "avro.codec"];
+
+/// The entries both writers put in the header, as in metadata_fixture.ml.
+/// avro-simple's Container_reader reads the header from the first 8 KiB of
+/// the file, so the largest value stays well below that.
+fn expected_entries() -> BTreeMap<String, Vec<u8>> {
+    BTreeMap::from([
+        ("lineage.pipeline".to_string(), b"people-daily".to_vec()),
+        (
+            "lineage.upstream".to_string(),
+            b"warehouse/people/2024-01-01/part-0000.avro".to_vec(),
+        ),
+        (
+            "lineage.note".to_string(),
+            "Z\u{fc}rich \u{2192} \u{6771}\u{4eac}".as_bytes().to_vec(),
+        ),
+        ("binary.all-bytes".to_string(), (0..=255).collect()),
+        ("binary.empty".to_string(), Vec::new()),
+        (
+            "binary.4k".to_string(),
+            (0..4096u32).map(|i| (i * 31 % 256) as u8).collect(),
+        ),
+    ])
+}
+
+// Writer must refuse every reserved key, the spec's own included
+fn check_reserved_rejected(schema: &Schema) -> Result<Vec<&'static str>> {
+    let keys = ["avro.schema", "avro.codec", "avro.lineage"];
+    for key in keys {
+        let mut writer = Writer::new(schema, Vec::new());
+        if writer.add_user_metadata(key.to_string(), b"x").is_ok() {
+            return Err(format!(
+                "Writer::add_user_metadata accepted the reserved key {}",
+                key
+            )
+            .into());
+        }
+    }
+    Ok(keys.to_vec())
+}
+
+fn write_with_metadata(
+    schema: &Schema,
+    options: &Options,
+    entries: &BTreeMap<String, Vec<u8>>,
+    path: &str,
+) -> Result<usize> {
+    let people = dataset::people(options)?;
+    let mut writer = Writer::builder()
+        .schema(schema)
+        .writer(BufWriter::new(File::create(path)?))
+        .codec(ApacheBackend::codec(&options.compression)?)
+        .maybe_block_size(options.block_size)
+        .build();
+    for (key, value) in entries {
+        writer.add_user_metadata(key.clone(), value)?;
+    }
+    for person in &people {
+        writer.append_ser(person)?;
+    }
+    writer.flush()?;
+    Ok(people.len())
+}
+
+// One line per expected or found key; returns the number of problems
+fn compare(
+    expected: &BTreeMap<String, Vec<u8>>,
+    found: &HashMap<String, Vec<u8>>,
+    source: &str,
+) -> usize {
+    let keys: BTreeSet<&String> = expected.keys().chain(found.keys()).collect();
+    let mut problems = 0;
+    for key in keys {
+        let (status, detail) = match (expected.get(key), found.get(key)) {
+            (Some(expected), Some(found)) if expected == found => ("ok", String::new()),
+            (Some(expected), Some(found)) => {
+                let offset = expected
+                    .iter()
+                    .zip(found)
+                    .position(|(a, b)| a != b)
+                    .unwrap_or(expected.len().min(found.len()));
+                let detail = format!(
+                    "{} in {}, expected {} bytes; first difference at byte {}",
+                    describe_bytes(found),
+                    source,
+                    expected.len(),
+                    offset
+                );
+                ("DIFFERS", detail)
+            }
+            (Some(_), None) => ("MISSING", String::new()),
+            (None, Some(found)) => ("EXTRA", describe_bytes(found)),
+            (None, None) => unreachable!(),
+        };
+        if status != "ok" {
+            problems += 1;
+        }
+        let len = found.get(key).or(expected.get(key)).map_or(0, Vec::len);
+        let line = format!("  {:<8} {:<18} {:>5} bytes  {}", status, key, len, detail);
+        println!("{}", line.trim_end());
+    }
+    problems
+}
+
+/// `metadata [count] [compression] [--input <file.avro>] [--output <file.avro>]`
+///
+/// Without `--input`, a Person container is written with the expected entries
+/// and read back. With it, a file from another writer (metadata_fixture.ml)
+/// must carry the same entries and no unknown reserved keys.
+pub fn metadata(input: Option<&str>, output: Option<&str>, options: &Options) -> Result<()> {
+    let schema = Schema::parse_str(PERSON_SCHEMA)?;
+    let expected = expected_entries();
+
+    let rejected = check_reserved_rejected(&schema)?;
+    println!("Reserved keys refused by Writer: {}", rejected.join(", "));
+
+    let temp_path = std::env::temp_dir().join(format!(
+        "bench_metadata_{}.avro",
+        ApacheBackend::IMPLEMENTATION
+    ));
+    let path = match input {
+        Some(path) => path.to_string(),
+        None => {
+            let path = temp_path.to_string_lossy().into_owned();
+            let count = write_with_metadata(&schema, options, &expected, &path)?;
+            println!(
+                "Wrote {} records and {} entries to {}",
+                count,
+                expected.len(),
+                path
+            );
+            path
+        }
+    };
+
+    let header = read_file_metadata(&path)?;
+    let reader = Reader::new(BufReader::new(File::open(&path)?))?;
+    let user_metadata = reader.user_metadata().clone();
+    let mut records = 0;
+    for value in reader {
+        value?;
+        records += 1;
+    }
+    println!("File: {} ({} records)", path, records);
+
+    println!("Reader::user_metadata:");
+    let mut problems = compare(&expected, &user_metadata, "Reader::user_metadata");
+    // The entries must be in the header itself, not only seen through Reader
+    let header_user: HashMap<String, Vec<u8>> = header
+        .iter()
+        .filter(|(key, _)| !key.starts_with(RESERVED_PREFIX))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    if header_user != user_metadata {
+        println!("  Reader::user_metadata differs from the entries in the header");
+        problems += 1;
+    }
+    let mut reserved: Vec<&String> = header
+        .keys()
+        .filter(|key| key.starts_with(RESERVED_PREFIX) && !SPEC_KEYS.contains(&key.as_str()))
+        .collect();
+    reserved.sort();
+    for key in &reserved {
+        println!(
+            "  REJECTED {} = {} (reserved prefix; Reader ignores it)",
+            key,
+            describe_bytes(&header[*key])
+        );
+    }
+    problems += reserved.len();
+
+    if input.is_none() {
+        // Copied rather than renamed, as the temp dir may be another filesystem
+        if let Some(output) = output {
+            std::fs::copy(&temp_path, output)?;
+            println!("Kept the file as {}", output);
+        }
+        std::fs::remove_file(&temp_path)?;
+    }
+    if problems > 0 {
+        return Err(format!("{} metadata problems in {}", problems, path).into());
+    }
+    println!("All {} entries round-tripped byte for byte", expected.len());
+    Ok(())
+}
@@ -0,0 +1,328 @@
+// apache-avro encode/decode strategies (`--mode`)
+// The plain encode and decode benchmarks use one idiom: serde through an
+// intermediate Value. apache-avro offers others, and this runs the Person
+// workload through each so the comparison with serde_avro_fast is not just
+// against the slowest path:
+//
+// - serde: to_value + to_avro_datum, from_avro_datum + from_value
+// - value: Value::Record built and taken apart by hand, no serde
+// - single-object: SpecificSingleObjectWriter/Reader, one message per record
+// - writer: one Writer (or Reader) over an in-memory buffer, with append_ser
+
+use crate::backend::ApacheBackend;
+use apache_avro::types::Value;
+use apache_avro::{
+    from_avro_datum, from_value, to_avro_datum, to_value, AvroSchema, Reader, Schema,
+    SpecificSingleObjectReader, SpecificSingleObjectWriter, Writer,
+};
+use avro_rust_bench_common::cli::Options;
+use avro_rust_bench_common::dataset;
+use avro_rust_bench_common::harness;
+use avro_rust_bench_common::report::{Reporter, ResultRecord};
+use avro_rust_bench_common::workloads::print_stats;
+use avro_rust_bench_common::{AvroBackend, Person, Result, PERSON_SCHEMA};
+use serde::Deserialize;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Serde,
+    Value,
+    SingleObject,
+    Writer,
+}
+
+pub const MODES: [Mode; 4] = [Mode::Serde, Mode::Value, Mode::SingleObject, Mode::Writer];
+
+impl Mode {
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Serde => "serde",
+            Mode::Value => "value",
+            Mode::SingleObject => "single-object",
+            Mode::Writer => "writer",
+        }
+    }
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        MODES
+            .into_iter()
+            .find(|mode| mode.name() == s)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown mode '{}' (expected serde, value, single-object, writer or all)",
+                    s
+                )
+            })
+    }
+}
+
+/// Parse a `--mode` value: one mode, a comma-separated list, or `all`.
+pub fn parse_modes(value: &str) -> std::result::Result<Vec<Mode>, String> {
+    if value == "all" {
+        return Ok(MODES.to_vec());
+    }
+    value.split(',').map(str::parse).collect()
+}
+
+// SpecificSingleObjectWriter/Reader need an AvroSchema type, and Person lives
+// in the common crate. Borrowed for writing, owned for reading.
+#[derive(Deserialize)]
+#[serde(transparent)]
+struct SpecificPerson<P>(P);
+
+impl<P> AvroSchema for SpecificPerson<P> {
+    fn get_schema() -> Schema {
+        Schema::parse_str(PERSON_SCHEMA).expect("PERSON_SCHEMA is valid")
+    }
+}
+
+// SpecificSingleObjectWriter::write_ref only writes the header before the
+// first record; write_value writes it for every message
+impl From<SpecificPerson<&Person>> for Value {
+    fn from(person: SpecificPerson<&Person>) -> Value {
+        person_to_value(person.0)
+    }
+}
+
+fn person_to_value(person: &Person) -> Value {
+    let email = match &person.email {
+        None => Value::Union(0, Box::new(Value::Null)),
+        Some(email) => Value::Union(1, Box::new(Value::String(email.clone()))),
+    };
+    Value::Record(vec![
+        ("name".to_string(), Value::String(person.name.clone())),
+        ("age".to_string(), Value::Int(person.age)),
+        ("email".to_string(), email),
+        (
+            "phone_numbers".to_string(),
+            Value::Array(
+                person
+                    .phone_numbers
+                    .iter()
+                    .map(|phone| Value::String(phone.clone()))
+                    .collect(),
+            ),
+        ),
+    ])
+}
+
+fn value_to_person(value: Value) -> Result<Person> {
+    let Value::Record(fields) = value else {
+        return Err(format!("expected a record, got {:?}", value).into());
+    };
+    let mut fields = fields.into_iter().map(|(_, value)| value);
+    let mut next = || fields.next().ok_or("Person is missing fields");
+
+    Ok(Person {
+        name: match next()? {
+            Value::String(name) => name,
+            other => return Err(unexpected("name", other)),
+        },
+        age: match next()? {
+            Value::Int(age) => age,
+            other => return Err(unexpected("age", other)),
+        },
+        email: match next()? {
+            Value::Union(_, email) => match *email {
+                Value::Null => None,
+                Value::String(email) => Some(email),
+                other => return Err(unexpected("email", other)),
+            },
+            other => return Err(unexpected("email", other)),
+        },
+        phone_numbers: match next()? {
+            Value::Array(phones) => phones
+                .into_iter()
+                .map(|phone| match phone {
+                    Value::String(phone) => Ok(phone),
+                    other => Err(unexpected("phone_numbers", other)),
+                })
+                .collect::<Result<_>>()?,
+            other => return Err(unexpected("phone_numbers", other)),
+        },
+    })
+}
+
+fn unexpected(field: &str, value: Value) -> avro_rust_bench_common::Error {
+    format!("unexpected value for {}: {:?}", field, value).into()
+}
+
+struct Strategies {
+    schema: Schema,
+    single_object_reader: SpecificSingleObjectReader<SpecificPerson<Person>>,
+    buffer: Vec<u8>,
+}
+
+impl Strategies {
+    fn new() -> Result<Strategies> {
+        Ok(Strategies {
+            schema: Schema::parse_str(PERSON_SCHEMA)?,
+            single_object_reader: SpecificSingleObjectReader::new()?,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Encode every record, one buffer each. `writer` instead leaves a single
+    /// container file in the reused buffer and returns no buffers.
+    fn encode(&mut self, mode: Mode, people: &[Person]) -> Result<Vec<Vec<u8>>> {
+        let schema = &self.schema;
+        match mode {
+            Mode::Serde => people
+                .iter()
+                .map(|person| Ok(to_avro_datum(schema, to_value(person)?)?))
+                .collect(),
+            Mode::Value => people
+                .iter()
+                .map(|person| Ok(to_avro_datum(schema, person_to_value(person))?))
+                .collect(),
+            Mode::SingleObject => {
+                // Typed by the borrow, so made per pass rather than kept
+                let mut writer =
+                    SpecificSingleObjectWriter::<SpecificPerson<&Person>>::with_capacity(1024)?;
+                people
+                    .iter()
+                    .map(|person| {
+                        let mut message = Vec::new();
+                        writer.write_value(SpecificPerson(person), &mut message)?;
+                        Ok(message)
+                    })
+                    .collect()
+            }
+            Mode::Writer => {
+                self.buffer.clear();
+                let mut writer = Writer::new(schema, &mut self.buffer);
+                for person in people {
+                    writer.append_ser(person)?;
+                }
+                writer.flush()?;
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    /// Decode everything `encode` produced for `mode`, handing each record to
+    /// `f` with its index.
+    fn decode(
+        &self,
+        mode: Mode,
+        encoded: &[Vec<u8>],
+        mut f: impl FnMut(usize, Person) -> Result<()>,
+    ) -> Result<()> {
+        let schema = &self.schema;
+        match mode {
+            Mode::Serde | Mode::Value | Mode::SingleObject => {
+                for (i, bytes) in encoded.iter().enumerate() {
+                    let mut reader = &bytes[..];
+                    let person = match mode {
+                        Mode::Serde => from_value(&from_avro_datum(schema, &mut reader, None)?)?,
+                        Mode::Value => {
+                            value_to_person(from_avro_datum(schema, &mut reader, None)?)?
+                        }
+                        _ => self.single_object_reader.read(&mut reader)?.0,
+                    };
+                    f(i, person)?;
+                }
+            }
+            Mode::Writer => {
+                let reader = Reader::with_schema(schema, &self.buffer[..])?;
+                for (i, value) in reader.enumerate() {
+                    f(i, from_value(&value?)?)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn benchmark_modes(modes: &[Mode], options: &Options, reporter: &Reporter) -> Result<()> {
+    let people = dataset::people(options)?;
+    let mut strategies = Strategies::new()?;
+    let operation = options.operation.as_str();
+
+    let mut results = Vec::new();
+    for &mode in modes {
+        let encoded = strategies.encode(mode, &people)?;
+        let total_bytes = match mode {
+            Mode::Writer => strategies.buffer.len(),
+            _ => encoded.iter().map(Vec::len).sum(),
+        };
+
+        let stats = match operation {
+            "encode" => harness::measure(&options.harness, || strategies.encode(mode, &people))?,
+            "decode" => {
+                // Round-trip check, outside the timed section
+                let mut decoded = 0;
+                strategies.decode(mode, &encoded, |i, person| {
+                    decoded += 1;
+                    match people.get(i) {
+                        Some(expected) if *expected == person => Ok(()),
+                        expected => Err(format!(
+                            "{}: round-trip mismatch at record {}: expected {:?}, got {:?}",
+                            mode.name(),
+                            i,
+                            expected,
+                            person
+                        )
+                        .into()),
+                    }
+                })?;
+                if decoded != people.len() {
+                    return Err(format!(
+                        "{}: decoded {} records, expected {}",
+                        mode.name(),
+                        decoded,
+                        people.len()
+                    )
+                    .into());
+                }
+                harness::measure(&options.harness, || {
+                    strategies.decode(mode, &encoded, |_, person| {
+                        std::hint::black_box(person);
+                        Ok(())
+                    })
+                })?
+            }
+            op => return Err(format!("--mode does not support {}", op).into()),
+        };
+
+        let record = ResultRecord::measured(
+            ApacheBackend::IMPLEMENTATION,
+            &format!("{}-{}", operation, mode.name()),
+            people.len(),
+            total_bytes as u64,
+            &stats,
+        );
+        results.push((mode, record, stats));
+    }
+
+    let records: Vec<ResultRecord> = results
+        .iter()
+        .map(|(_, record, _)| record.clone())
+        .collect();
+    reporter.emit(&records, || {
+        println!(
+            "=== apache-avro {} strategies ({} records) ===",
+            operation,
+            people.len()
+        );
+        let baseline = results[0].1.elapsed_secs;
+        for (mode, record, stats) in &results {
+            println!(
+                "  {:<14} {:.6} seconds ({:.2} MB/s, {} bytes, {:.2}x vs {})",
+                mode.name(),
+                record.elapsed_secs,
+                record.mb_per_sec,
+                record.bytes,
+                baseline / record.elapsed_secs,
+                results[0].0.name()
+            );
+            print_stats(stats, people.len());
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,385 @@
+// Parallel container decoding (`parallel-read`)
+// apache-avro's Reader decompresses and decodes one block at a time. Blocks are
+// compressed independently, so once their boundaries are known they can be
+// handed to a pool of threads. The scan reads each block's record count and
+// byte size and checks that the sync marker follows, without decompressing
+// anything. Workers take the next undecoded block from a shared counter and
+// drop its records once checked, so only the record counts are gathered. The
+// file is read into memory first, so the timings cover the scan, decompression
+// and decoding; memory use is the file plus one decompressed block per thread.
+
+use crate::backend::{has_refs, ApacheBackend};
+use crate::container::{read_metadata, SYNC_LEN};
+use apache_avro::types::Value;
+use apache_avro::{from_avro_datum, from_avro_datum_schemata, from_value, Codec, Reader, Schema};
+use avro_rust_bench_common::cli::Options;
+use avro_rust_bench_common::harness::{self, Stats};
+use avro_rust_bench_common::report::{Reporter, ResultRecord};
+use avro_rust_bench_common::workloads::print_stats;
+use avro_rust_bench_common::{dataset, AvroBackend, Person, Result, PERSON_SCHEMA};
+use std::hint::black_box;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+struct Block<'a> {
+    /// Index in the file of the block's first record
+    first: usize,
+    count: usize,
+    data: &'a [u8],
+}
+
+/// A container file split into its blocks, still compressed.
+struct Container<'a> {
+    schema: Schema,
+    has_refs: bool,
+    codec: Codec,
+    sync: [u8; SYNC_LEN],
+    blocks: Vec<Block<'a>>,
+}
+
+impl Container<'_> {
+    fn records(&self) -> usize {
+        self.blocks.iter().map(|block| block.count).sum()
+    }
+
+    // from_avro_datum resolves the schema's names on every call, which Reader
+    // does once per file. Only references to named types need them.
+    fn decode_datum(&self, datum: &mut &[u8]) -> Result<Value> {
+        if self.has_refs {
+            Ok(from_avro_datum(&self.schema, datum, None)?)
+        } else {
+            Ok(from_avro_datum_schemata(
+                &self.schema,
+                Vec::new(),
+                datum,
+                None,
+            )?)
+        }
+    }
+}
+
+// Zig-zag varint, as block counts and sizes are written
+fn read_long(bytes: &[u8], pos: &mut usize) -> Result<i64> {
+    let mut value: u64 = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes
+            .get(*pos)
+            .ok_or("container file ends inside a block header")?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok((value >> 1) as i64 ^ -((value & 1) as i64));
+        }
+    }
+    Err("varint longer than 10 bytes in a block header".into())
+}
+
+fn scan(bytes: &[u8]) -> Result<Container<'_>> {
+    let mut rest = bytes;
+    let metadata = read_metadata(&mut rest)?;
+    let text = |key: &str| -> Result<Option<String>> {
+        match metadata.get(key) {
+            Some(bytes) => Ok(Some(String::from_utf8(bytes.clone())?)),
+            None => Ok(None),
+        }
+    };
+    let schema = Schema::parse_str(&text("avro.schema")?.ok_or("header without avro.schema")?)?;
+    let codec_name = text("avro.codec")?.unwrap_or_else(|| "null".to_string());
+    let codec = Codec::from_str(&codec_name)
+        .map_err(|_| format!("unsupported codec '{}' in this build", codec_name))?;
+
+    let mut pos = bytes.len() - rest.len();
+    let sync: [u8; SYNC_LEN] = bytes
+        .get(pos..pos + SYNC_LEN)
+        .ok_or("container header without a sync marker")?
+        .try_into()?;
+    pos += SYNC_LEN;
+
+    let mut blocks = Vec::new();
+    let mut records: usize = 0;
+    while pos < bytes.len() {
+        let start = pos;
+        let count = read_long(bytes, &mut pos)?;
+        let size = read_long(bytes, &mut pos)?;
+        if count < 0 || size < 0 {
+            return Err(format!("negative block count or size at offset {}", start).into());
+        }
+        let past_end = || {
+            format!(
+                "block at offset {} runs past the end of the file ({} bytes)",
+                start, size
+            )
+        };
+        let end = usize::try_from(size)
+            .ok()
+            .and_then(|size| pos.checked_add(size))
+            .ok_or_else(past_end)?;
+        let data = bytes.get(pos..end).ok_or_else(past_end)?;
+        let sync_end = end.checked_add(SYNC_LEN).ok_or_else(past_end)?;
+        if bytes.get(end..sync_end) != Some(&sync[..]) {
+            return Err(format!(
+                "block at offset {} is not followed by the sync marker",
+                start
+            )
+            .into());
+        }
+        let count = usize::try_from(count)
+            .ok()
+            .filter(|&count| records.checked_add(count).is_some())
+            .ok_or_else(|| format!("block at offset {} overflows the record count", start))?;
+        blocks.push(Block {
+            first: records,
+            count,
+            data,
+        });
+        records += count;
+        pos = sync_end;
+    }
+    Ok(Container {
+        has_refs: has_refs(&schema),
+        schema,
+        codec,
+        sync,
+        blocks,
+    })
+}
+
+/// A container file's header and block layout, found without decompressing
+/// any block. Every block has been checked to end with `sync`.
+pub(crate) struct Summary {
+    pub(crate) schema: Schema,
+    pub(crate) codec: Codec,
+    pub(crate) sync: [u8; SYNC_LEN],
+    pub(crate) blocks: usize,
+    pub(crate) records: usize,
+}
+
+pub(crate) fn summarize(bytes: &[u8]) -> Result<Summary> {
+    let container = scan(bytes)?;
+    Ok(Summary {
+        blocks: container.blocks.len(),
+        records: container.records(),
+        sync: container.sync,
+        schema: container.schema,
+        codec: container.codec,
+    })
+}
+
+// `check` sees each record with its index in the file, then it is dropped
+fn decode_block<T>(
+    container: &Container,
+    block: &Block,
+    decode: &(impl Fn(Value) -> Result<T> + Sync),
+    check: &(impl Fn(usize, &T) -> Result<()> + Sync),
+) -> Result<usize> {
+    let mut data = block.data.to_vec();
+    container.codec.decompress(&mut data)?;
+    let mut rest = &data[..];
+    for i in 0..block.count {
+        let record = decode(container.decode_datum(&mut rest)?)?;
+        check(block.first + i, &record)?;
+        black_box(record);
+    }
+    if !rest.is_empty() {
+        return Err(format!(
+            "{} bytes left over after the {} records of a block",
+            rest.len(),
+            block.count
+        )
+        .into());
+    }
+    Ok(block.count)
+}
+
+/// Decode every block on `threads` threads, passing each record to `check`,
+/// and return how many were decoded.
+fn decode_parallel<T>(
+    container: &Container,
+    threads: usize,
+    decode: impl Fn(Value) -> Result<T> + Sync,
+    check: impl Fn(usize, &T) -> Result<()> + Sync,
+) -> Result<usize> {
+    let next = AtomicUsize::new(0);
+    let worker = || -> Result<usize> {
+        let mut decoded = 0;
+        loop {
+            let index = next.fetch_add(1, Ordering::Relaxed);
+            let Some(block) = container.blocks.get(index) else {
+                return Ok(decoded);
+            };
+            match decode_block(container, block, &decode, &check) {
+                Ok(records) => decoded += records,
+                Err(err) => {
+                    // Leave nothing for the other workers
+                    next.store(container.blocks.len(), Ordering::Relaxed);
+                    return Err(err);
+                }
+            }
+        }
+    };
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads).map(|_| scope.spawn(worker)).collect();
+        workers
+            .into_iter()
+            .map(|handle| handle.join().expect("decoder thread panicked"))
+            .sum()
+    })
+}
+
+// Records are not needed after decoding
+fn unchecked<T>(_: usize, _: &T) -> Result<()> {
+    Ok(())
+}
+
+/// 1, 2, 4, ... up to and including `max`.
+pub(crate) fn thread_counts(max: usize) -> Vec<usize> {
+    let mut counts: Vec<usize> = (0..)
+        .map(|shift| 1 << shift)
+        .take_while(|&n| n < max)
+        .collect();
+    counts.push(max);
+    counts
+}
+
+fn to_person(value: Value) -> Result<Person> {
+    Ok(from_value(&value)?)
+}
+
+// The baseline drops its records too, as decode_parallel does
+fn read_with_reader<T>(bytes: &[u8], decode: impl Fn(Value) -> Result<T>) -> Result<usize> {
+    Reader::new(bytes)?.try_fold(0, |read, value| {
+        black_box(decode(value?)?);
+        Ok(read + 1)
+    })
+}
+
+fn person_container(
+    people: &[Person],
+    compression: &str,
+    block_size: Option<usize>,
+) -> Result<Vec<u8>> {
+    let mut backend = ApacheBackend::new(PERSON_SCHEMA)?;
+    let codec = ApacheBackend::codec(compression)?;
+    let path = std::env::temp_dir().join("bench_parallel_read.avro");
+    backend.write_container(people, codec, block_size, &path)?;
+    let bytes = std::fs::read(&path)?;
+    std::fs::remove_file(&path)?;
+    Ok(bytes)
+}
+
+/// `parallel-read [count] [compression] [--threads N] [--block-size N] [--input <file.avro>]`
+///
+/// Without `--input`, a Person container is written with the given codec and
+/// every record is checked after decoding. An `--input` file may have any
+/// schema and is decoded to `Value`s only.
+pub fn benchmark_parallel_read(
+    threads: usize,
+    input: Option<&str>,
+    options: &Options,
+    reporter: &Reporter,
+) -> Result<()> {
+    let (bytes, people) = match input {
+        Some(path) => (std::fs::read(path)?, None),
+        None => {
+            let people = dataset::people(options)?;
+            let bytes = person_container(&people, &options.compression, options.block_size)?;
+            (bytes, Some(people))
+        }
+    };
+    let container = scan(&bytes)?;
+    let codec_name: &str = container.codec.into();
+    let count = container.records();
+    let is_person = people.is_some();
+
+    // Order and content check, outside the timed section
+    if let Some(people) = &people {
+        let matches = |i: usize, person: &Person| -> Result<()> {
+            if people.get(i) != Some(person) {
+                return Err(
+                    format!("parallel-read record {} differs from the record written", i).into(),
+                );
+            }
+            Ok(())
+        };
+        let read = decode_parallel(&container, threads, to_person, matches)?;
+        if read != people.len() {
+            return Err(format!("parallel-read read {} of {} records", read, people.len()).into());
+        }
+    }
+
+    let check = |read: usize| -> Result<()> {
+        if read != count {
+            return Err(format!("read {} records, expected {}", read, count).into());
+        }
+        Ok(())
+    };
+    let reader_stats = harness::measure(&options.harness, || {
+        check(match is_person {
+            true => read_with_reader(&bytes, to_person)?,
+            false => read_with_reader(&bytes, Ok)?,
+        })
+    })?;
+    let mut scaling: Vec<(usize, Stats)> = Vec::new();
+    for n in thread_counts(threads) {
+        let stats = harness::measure(&options.harness, || {
+            check(match is_person {
+                true => decode_parallel(&container, n, to_person, unchecked)?,
+                false => decode_parallel(&container, n, Ok, unchecked)?,
+            })
+        })?;
+        scaling.push((n, stats));
+    }
+
+    let bytes_len = bytes.len() as u64;
+    let mut records = vec![ResultRecord::measured(
+        ApacheBackend::IMPLEMENTATION,
+        "container-read",
+        count,
+        bytes_len,
+        &reader_stats,
+    )
+    .with_codec(codec_name)
+    .with_block_size(options.block_size)];
+    records.extend(scaling.iter().map(|(n, stats)| {
+        ResultRecord::measured(
+            ApacheBackend::IMPLEMENTATION,
+            &format!("parallel-read-{}", n),
+            count,
+            bytes_len,
+            stats,
+        )
+        .with_codec(codec_name)
+        .with_block_size(options.block_size)
+    }));
+
+    reporter.emit(&records, || {
+        println!(
+            "=== Parallel Block Decoding ({} records, {} blocks, {} bytes, {}) ===",
+            count,
+            container.blocks.len(),
+            bytes_len,
+            codec_name
+        );
+        println!(
+            "  Reader     {:.6} seconds ({:.2} MB/s)",
+            reader_stats.mean, records[0].mb_per_sec
+        );
+        print_stats(&reader_stats, count);
+        let single = scaling[0].1.mean;
+        for ((n, stats), record) in scaling.iter().zip(&records[1..]) {
+            println!(
+                "  {:>2} thread{} {:.6} seconds ({:.2} MB/s), {:.2}x one thread, {:.0}% efficiency",
+                n,
+                if *n == 1 { " " } else { "s" },
+                stats.mean,
+                record.mb_per_sec,
+                single / stats.mean,
+                single / stats.mean / *n as f64 * 100.0
+            );
+            print_stats(stats, count);
+        }
+    });
+    Ok(())
+}
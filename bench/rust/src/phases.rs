@@ -0,0 +1,104 @@
+// Phase breakdown for apache-avro encode (`encode --phases`)
+// ApacheBackend::encode_datum goes through an intermediate Value. This times
+// each step on its own: building the Person records, or loading them with
+// --dataset (excluded from the plain encode timing), serde conversion with
+// to_value, and Value encoding with to_avro_datum, next to the combined path
+// the encode benchmark measures.
+
+use crate::backend::ApacheBackend;
+use apache_avro::types::Value;
+use apache_avro::{to_avro_datum, to_value, Schema};
+use avro_rust_bench_common::cli::Options;
+use avro_rust_bench_common::dataset;
+use avro_rust_bench_common::harness::{self, Stats};
+use avro_rust_bench_common::report::{Reporter, ResultRecord};
+use avro_rust_bench_common::workloads::print_stats;
+use avro_rust_bench_common::{AvroBackend, Person, Result, PERSON_SCHEMA};
+
+fn to_values(people: &[Person]) -> Result<Vec<Value>> {
+    Ok(people
+        .iter()
+        .map(to_value)
+        .collect::<std::result::Result<_, _>>()?)
+}
+
+pub fn benchmark_encode_phases(options: &Options, reporter: &Reporter) -> Result<()> {
+    let schema = Schema::parse_str(PERSON_SCHEMA)?;
+    let mut backend = ApacheBackend::new(PERSON_SCHEMA)?;
+    let people = dataset::people(options)?;
+
+    // With --dataset the records come from the file, so its load is timed
+    let (input_operation, input_label) = match &options.dataset {
+        Some(_) => ("encode-load", "load dataset"),
+        None => ("encode-generate", "create_people"),
+    };
+    let input_stats = harness::measure(&options.harness, || dataset::people(options))?;
+
+    let to_value_stats = harness::measure(&options.harness, || to_values(&people))?;
+
+    // to_avro_datum consumes its Value, so each iteration gets fresh ones
+    let mut total_bytes = 0;
+    let to_datum_stats = harness::measure_with_setup(
+        &options.harness,
+        || to_values(&people),
+        |values| {
+            total_bytes = 0;
+            for value in values {
+                total_bytes += to_avro_datum(&schema, value)?.len();
+            }
+            Ok(())
+        },
+    )?;
+
+    let encode_stats = harness::measure(&options.harness, || {
+        for person in &people {
+            backend.encode_datum(person)?;
+        }
+        Ok(())
+    })?;
+
+    // Every phase reports the encoded size, so MB/s compare directly
+    let phases: [(&str, &str, &Stats); 4] = [
+        (input_operation, input_label, &input_stats),
+        ("encode-to-value", "to_value", &to_value_stats),
+        ("encode-to-datum", "to_avro_datum", &to_datum_stats),
+        ("encode", "to_value + to_avro_datum", &encode_stats),
+    ];
+    let records: Vec<ResultRecord> = phases
+        .iter()
+        .map(|(operation, _, stats)| {
+            ResultRecord::measured(
+                ApacheBackend::IMPLEMENTATION,
+                operation,
+                people.len(),
+                total_bytes as u64,
+                stats,
+            )
+        })
+        .collect();
+
+    reporter.emit(&records, || {
+        println!(
+            "=== Encode Phase Breakdown ({} records, {} bytes) ===",
+            people.len(),
+            total_bytes
+        );
+        for ((_, label, stats), record) in phases.iter().zip(&records) {
+            println!(
+                "  {:<26} {:.6} seconds ({:>5.1}% of encode, {:.0} records/sec)",
+                label,
+                stats.mean,
+                stats.mean * 100.0 / encode_stats.mean,
+                record.records_per_sec
+            );
+            print_stats(stats, people.len());
+        }
+        let convert_and_encode = to_value_stats.mean + to_datum_stats.mean;
+        println!(
+            "Of to_value + to_avro_datum: {:.1}% serde conversion, {:.1}% Value encoding",
+            to_value_stats.mean * 100.0 / convert_and_encode,
+            to_datum_stats.mean * 100.0 / convert_and_encode
+        );
+    });
+    Ok(())
+}
@@ -0,0 +1,373 @@
+// Pipelined container writer (`pipelined-write`)
+// apache-avro's Writer encodes, compresses and writes each block in turn on
+// the calling thread. Here the caller only encodes: each full block goes to a
+// pool of compression threads, and one output thread puts the compressed
+// blocks back in order and writes them with the file's sync marker. Bounded
+// channels keep at most two blocks per worker in flight. The file layout is
+// the one Writer produces, so Reader and avro-simple's Container_reader read it
+// unchanged.
+
+use crate::backend::{has_refs, ApacheBackend};
+use crate::container::{metadata_schema, MAGIC, SYNC_LEN};
+use crate::parallel::thread_counts;
+use apache_avro::types::Value;
+use apache_avro::{
+    from_value, to_avro_datum, to_value, write_avro_datum_ref, Codec, Reader, Schema,
+};
+use avro_rust_bench_common::cli::Options;
+use avro_rust_bench_common::harness::{self, Stats};
+use avro_rust_bench_common::report::{Reporter, ResultRecord};
+use avro_rust_bench_common::rng::Rng;
+use avro_rust_bench_common::workloads::print_stats;
+use avro_rust_bench_common::{dataset, AvroBackend, Person, Result, PERSON_SCHEMA};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Uncompressed block size that triggers a flush unless `--block-size` is
+/// given, as in apache-avro's Writer.
+const BLOCK_SIZE: usize = 16_000;
+
+struct RawBlock {
+    index: usize,
+    count: usize,
+    data: Vec<u8>,
+}
+
+fn write_long(out: &mut impl Write, value: i64) -> std::io::Result<()> {
+    let mut zigzag = ((value << 1) ^ (value >> 63)) as u64;
+    let mut bytes = Vec::with_capacity(10);
+    while zigzag >= 0x80 {
+        bytes.push((zigzag as u8 & 0x7f) | 0x80);
+        zigzag >>= 7;
+    }
+    bytes.push(zigzag as u8);
+    out.write_all(&bytes)
+}
+
+fn sync_marker() -> [u8; SYNC_LEN] {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+    let mut rng = Rng::new(nanos ^ u64::from(std::process::id()));
+    let mut marker = [0; SYNC_LEN];
+    marker[..8].copy_from_slice(&rng.next_u64().to_le_bytes());
+    marker[8..].copy_from_slice(&rng.next_u64().to_le_bytes());
+    marker
+}
+
+// Magic, the metadata map and the sync marker, as Writer writes them
+fn header(schema: &Schema, codec: Codec, sync: &[u8; SYNC_LEN]) -> Result<Vec<u8>> {
+    let codec_name: &str = codec.into();
+    let metadata = HashMap::from([
+        (
+            "avro.schema".to_string(),
+            Value::Bytes(serde_json::to_string(schema)?.into_bytes()),
+        ),
+        (
+            "avro.codec".to_string(),
+            Value::Bytes(codec_name.as_bytes().to_vec()),
+        ),
+    ]);
+    let mut header = MAGIC.to_vec();
+    header.extend(to_avro_datum(&metadata_schema(), Value::Map(metadata))?);
+    header.extend_from_slice(sync);
+    Ok(header)
+}
+
+fn compress_blocks(
+    codec: Codec,
+    blocks: &Mutex<Receiver<RawBlock>>,
+    compressed: SyncSender<RawBlock>,
+) -> Result<()> {
+    loop {
+        // The lock is held only while waiting for the next block
+        let next = blocks.lock().unwrap().recv();
+        let Ok(mut block) = next else {
+            return Ok(());
+        };
+        codec.compress(&mut block.data)?;
+        if compressed.send(block).is_err() {
+            return Err("output thread stopped".into());
+        }
+    }
+}
+
+fn write_blocks<W: Write>(
+    mut out: W,
+    sync: [u8; SYNC_LEN],
+    compressed: Receiver<RawBlock>,
+) -> Result<W> {
+    let mut pending = BTreeMap::new();
+    let mut next = 0;
+    for block in compressed {
+        pending.insert(block.index, block);
+        while let Some(block) = pending.remove(&next) {
+            write_long(&mut out, block.count as i64)?;
+            write_long(&mut out, block.data.len() as i64)?;
+            out.write_all(&block.data)?;
+            out.write_all(&sync)?;
+            next += 1;
+        }
+    }
+    if !pending.is_empty() {
+        return Err(format!("{} blocks never reached the output thread", pending.len()).into());
+    }
+    out.flush()?;
+    Ok(out)
+}
+
+/// A container writer that compresses full blocks on `workers` threads.
+pub(crate) struct PipelinedWriter<'a, W> {
+    schema: &'a Schema,
+    has_refs: bool,
+    block_size: usize,
+    buffer: Vec<u8>,
+    count: usize,
+    next_index: usize,
+    blocks: Option<SyncSender<RawBlock>>,
+    workers: Vec<JoinHandle<Result<()>>>,
+    output: Option<JoinHandle<Result<W>>>,
+}
+
+impl<'a, W: Write + Send + 'static> PipelinedWriter<'a, W> {
+    pub(crate) fn new(
+        schema: &'a Schema,
+        codec: Codec,
+        block_size: usize,
+        mut out: W,
+        workers: usize,
+    ) -> Result<PipelinedWriter<'a, W>> {
+        let sync = sync_marker();
+        out.write_all(&header(schema, codec, &sync)?)?;
+
+        let (blocks, pool_input) = sync_channel::<RawBlock>(workers * 2);
+        let (compressed, output_input) = sync_channel::<RawBlock>(workers * 2);
+        let pool_input = Arc::new(Mutex::new(pool_input));
+        let workers = (0..workers)
+            .map(|_| {
+                let pool_input = Arc::clone(&pool_input);
+                let compressed = compressed.clone();
+                thread::spawn(move || compress_blocks(codec, &pool_input, compressed))
+            })
+            .collect();
+        // Only the workers' clones remain, so the output ends when they do
+        drop(compressed);
+        let output = thread::spawn(move || write_blocks(out, sync, output_input));
+
+        Ok(PipelinedWriter {
+            schema,
+            has_refs: has_refs(schema),
+            block_size,
+            buffer: Vec::with_capacity(block_size),
+            count: 0,
+            next_index: 0,
+            blocks: Some(blocks),
+            workers,
+            output: Some(output),
+        })
+    }
+
+    pub(crate) fn append<T: Serialize>(&mut self, value: &T) -> Result<()> {
+        if self.has_refs {
+            let datum = to_avro_datum(self.schema, to_value(value)?)?;
+            self.buffer.extend_from_slice(&datum);
+        } else {
+            write_avro_datum_ref(self.schema, value, &mut self.buffer)?;
+        }
+        self.count += 1;
+        if self.buffer.len() >= self.block_size {
+            self.send_block()?;
+        }
+        Ok(())
+    }
+
+    fn send_block(&mut self) -> Result<()> {
+        if self.count == 0 {
+            return Ok(());
+        }
+        let block = RawBlock {
+            index: self.next_index,
+            count: self.count,
+            data: std::mem::replace(&mut self.buffer, Vec::with_capacity(self.block_size)),
+        };
+        self.next_index += 1;
+        self.count = 0;
+        let sent = match &self.blocks {
+            Some(blocks) => blocks.send(block).is_ok(),
+            None => false,
+        };
+        if !sent {
+            // The pipeline has stopped; report why
+            return Err(match self.shutdown() {
+                Ok(_) => "compression threads stopped".into(),
+                Err(err) => err,
+            });
+        }
+        Ok(())
+    }
+
+    // Close the pipeline and wait for every thread. An output error is
+    // reported first, as the workers then only see a closed channel.
+    fn shutdown(&mut self) -> Result<W> {
+        self.blocks = None;
+        let workers: Vec<Result<()>> = self
+            .workers
+            .drain(..)
+            .map(|worker| worker.join().expect("compression thread panicked"))
+            .collect();
+        let output = self
+            .output
+            .take()
+            .ok_or("pipeline already shut down")?
+            .join()
+            .expect("output thread panicked")?;
+        workers.into_iter().collect::<Result<()>>()?;
+        Ok(output)
+    }
+
+    /// Write the last partial block and wait for everything to reach `W`.
+    pub(crate) fn finish(mut self) -> Result<W> {
+        self.send_block()?;
+        self.shutdown()
+    }
+}
+
+fn write_pipelined(
+    schema: &Schema,
+    codec: Codec,
+    block_size: usize,
+    people: &[Person],
+    workers: usize,
+    path: &Path,
+) -> Result<()> {
+    let out = BufWriter::new(File::create(path)?);
+    let mut writer = PipelinedWriter::new(schema, codec, block_size, out, workers)?;
+    for person in people {
+        writer.append(person)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+// Reader must see every record, in order
+fn check_file(path: &Path, people: &[Person]) -> Result<()> {
+    let reader = Reader::new(BufReader::new(File::open(path)?))?;
+    let mut read = 0;
+    for (i, value) in reader.enumerate() {
+        let person: Person = from_value(&value?)?;
+        if people.get(i) != Some(&person) {
+            return Err(format!(
+                "{}: record {} differs from the record written",
+                path.display(),
+                i
+            )
+            .into());
+        }
+        read += 1;
+    }
+    if read != people.len() {
+        return Err(format!(
+            "{}: Reader found {} records, expected {}",
+            path.display(),
+            read,
+            people.len()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// `pipelined-write [count] [compression] [--threads N] [--block-size N] [--output <file.avro>]`
+pub fn benchmark_pipelined_write(
+    threads: usize,
+    output: Option<&str>,
+    options: &Options,
+    reporter: &Reporter,
+) -> Result<()> {
+    let people = dataset::people(options)?;
+    let compression = options.compression.as_str();
+    let codec = ApacheBackend::codec(compression)?;
+    let schema = Schema::parse_str(PERSON_SCHEMA)?;
+    let mut backend = ApacheBackend::new(PERSON_SCHEMA)?;
+    let block_size = options.block_size.unwrap_or(BLOCK_SIZE);
+    let path = std::env::temp_dir().join(format!("bench_pipelined_{}.avro", compression));
+
+    let writer_stats = harness::measure(&options.harness, || {
+        backend.write_container(&people, codec, Some(block_size), &path)
+    })?;
+    let writer_size = std::fs::metadata(&path)?.len();
+
+    let mut scaling: Vec<(usize, u64, Stats)> = Vec::new();
+    for workers in thread_counts(threads) {
+        let stats = harness::measure(&options.harness, || {
+            write_pipelined(&schema, codec, block_size, &people, workers, &path)
+        })?;
+        check_file(&path, &people)?;
+        scaling.push((workers, std::fs::metadata(&path)?.len(), stats));
+    }
+    // Copied rather than renamed, as the temp dir may be another filesystem
+    if let Some(output) = output {
+        std::fs::copy(&path, output)?;
+    }
+    std::fs::remove_file(&path)?;
+
+    let count = people.len();
+    let mut records = vec![ResultRecord::measured(
+        ApacheBackend::IMPLEMENTATION,
+        "container-write",
+        count,
+        writer_size,
+        &writer_stats,
+    )
+    .with_codec(compression)
+    .with_block_size(Some(block_size))];
+    records.extend(scaling.iter().map(|(workers, size, stats)| {
+        ResultRecord::measured(
+            ApacheBackend::IMPLEMENTATION,
+            &format!("pipelined-write-{}", workers),
+            count,
+            *size,
+            stats,
+        )
+        .with_codec(compression)
+        .with_block_size(Some(block_size))
+    }));
+
+    reporter.emit(&records, || {
+        println!(
+            "=== Pipelined Container Writer ({} records, {}) ===",
+            count, compression
+        );
+        println!(
+            "  Writer     {:.6} seconds ({:.2} MB/s, {} bytes)",
+            writer_stats.mean, records[0].mb_per_sec, writer_size
+        );
+        print_stats(&writer_stats, count);
+        let single = scaling[0].2.mean;
+        for ((workers, size, stats), record) in scaling.iter().zip(&records[1..]) {
+            println!(
+                "  {:>2} worker{} {:.6} seconds ({:.2} MB/s, {} bytes), {:.2}x one worker, {:.2}x Writer",
+                workers,
+                if *workers == 1 { " " } else { "s" },
+                stats.mean,
+                record.mb_per_sec,
+                size,
+                single / stats.mean,
+                writer_stats.mean / stats.mean
+            );
+            print_stats(stats, count);
+        }
+        println!("Every file read back with apache_avro::Reader, all records in order");
+        if let Some(output) = output {
+            println!("Kept the {}-worker file as {}", scaling[scaling.len() - 1].0, output);
+        }
+    });
+    Ok(())
+}
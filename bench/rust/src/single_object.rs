@@ -0,0 +1,215 @@
+// Single-object encoding (`single-object`)
+// Person messages carry the C3 01 marker and the CRC-64-AVRO fingerprint of
+// the writer schema. Decoding looks the fingerprint up in a table of every
+// schema the bench knows, as a consumer of a mixed stream would. Before timing,
+// the header is checked against apache-avro's own single-object writer, and
+// unknown fingerprints and a bad marker must be rejected.
+
+use crate::backend::ApacheBackend;
+use apache_avro::headers::{HeaderBuilder, RabinFingerprintHeader};
+use apache_avro::types::Value;
+use apache_avro::{
+    from_avro_datum, from_value, to_avro_datum, to_value, GenericSingleObjectWriter, Schema,
+};
+use avro_rust_bench_common::cli::Options;
+use avro_rust_bench_common::dataset;
+use avro_rust_bench_common::fingerprint::{
+    crc64_avro, single_object_header, split_single_object, SINGLE_OBJECT_HEADER_LEN,
+};
+use avro_rust_bench_common::harness::{self, Stats};
+use avro_rust_bench_common::report::{Reporter, ResultRecord};
+use avro_rust_bench_common::workloads::print_stats;
+use avro_rust_bench_common::{
+    AvroBackend, Person, Result, LOGICAL_SCHEMA, PERSON_SCHEMA, TREE_SCHEMA,
+};
+use std::collections::HashMap;
+
+/// Writer schemas by fingerprint.
+struct SchemaTable {
+    schemas: HashMap<u64, Schema>,
+}
+
+impl SchemaTable {
+    fn new(schema_jsons: &[&str]) -> Result<SchemaTable> {
+        let mut schemas = HashMap::new();
+        for json in schema_jsons {
+            let schema = Schema::parse_str(json)?;
+            schemas.insert(fingerprint(&schema), schema);
+        }
+        Ok(SchemaTable { schemas })
+    }
+
+    fn decode(&self, message: &[u8]) -> Result<Value> {
+        let (fingerprint, mut datum) = split_single_object(message)?;
+        let schema = self.schemas.get(&fingerprint).ok_or_else(|| {
+            format!(
+                "unknown schema fingerprint {:016x} ({} schemas registered)",
+                fingerprint,
+                self.schemas.len()
+            )
+        })?;
+        Ok(from_avro_datum(schema, &mut datum, None)?)
+    }
+}
+
+fn fingerprint(schema: &Schema) -> u64 {
+    crc64_avro(schema.canonical_form().as_bytes())
+}
+
+fn encode_datum(schema: &Schema, person: &Person) -> Result<Vec<u8>> {
+    Ok(to_avro_datum(schema, to_value(person)?)?)
+}
+
+fn encode_message(schema: &Schema, header: &[u8], person: &Person) -> Result<Vec<u8>> {
+    let datum = encode_datum(schema, person)?;
+    let mut message = Vec::with_capacity(header.len() + datum.len());
+    message.extend_from_slice(header);
+    message.extend_from_slice(&datum);
+    Ok(message)
+}
+
+// Our header must match apache-avro's, and broken messages must fail cleanly.
+// Returns the error an unknown fingerprint gets, for the report.
+fn check_conformance(schema: &Schema, table: &SchemaTable, person: &Person) -> Result<String> {
+    let header = single_object_header(fingerprint(schema));
+    if header.to_vec() != RabinFingerprintHeader::from_schema(schema).build_header() {
+        return Err(format!(
+            "single-object header {:02x?} differs from apache-avro's {:02x?}",
+            header,
+            RabinFingerprintHeader::from_schema(schema).build_header()
+        )
+        .into());
+    }
+
+    let message = encode_message(schema, &header, person)?;
+    let mut expected = Vec::new();
+    GenericSingleObjectWriter::new_with_capacity(schema, 1024)?
+        .write_value(to_value(person)?, &mut expected)?;
+    if message != expected {
+        return Err("single-object message differs from GenericSingleObjectWriter's".into());
+    }
+
+    let mut unknown = message.clone();
+    unknown[2..SINGLE_OBJECT_HEADER_LEN].copy_from_slice(&crc64_avro(b"\"unknown\"").to_le_bytes());
+    let mut bad_magic = message.clone();
+    bad_magic[1] = 0x02;
+    let mut errors = Vec::new();
+    for (what, broken) in [("unknown fingerprint", unknown), ("bad marker", bad_magic)] {
+        match table.decode(&broken) {
+            Ok(_) => return Err(format!("a message with a {} was accepted", what).into()),
+            Err(err) => errors.push(err.to_string()),
+        }
+    }
+    Ok(errors.swap_remove(0))
+}
+
+pub fn benchmark_single_object(options: &Options, reporter: &Reporter) -> Result<()> {
+    let people = dataset::people(options)?;
+    let schema = Schema::parse_str(PERSON_SCHEMA)?;
+    let table = SchemaTable::new(&[PERSON_SCHEMA, LOGICAL_SCHEMA, TREE_SCHEMA])?;
+    let header = single_object_header(fingerprint(&schema));
+    let rejection = match people.first() {
+        Some(person) => check_conformance(&schema, &table, person)?,
+        None => return Err("single-object needs at least one record".into()),
+    };
+
+    let encode_stats = harness::measure(&options.harness, || {
+        people
+            .iter()
+            .map(|person| encode_datum(&schema, person))
+            .collect::<Result<Vec<_>>>()
+    })?;
+    let encode_single_stats = harness::measure(&options.harness, || {
+        people
+            .iter()
+            .map(|person| encode_message(&schema, &header, person))
+            .collect::<Result<Vec<_>>>()
+    })?;
+
+    let datums = people
+        .iter()
+        .map(|person| encode_datum(&schema, person))
+        .collect::<Result<Vec<_>>>()?;
+    let messages = people
+        .iter()
+        .map(|person| encode_message(&schema, &header, person))
+        .collect::<Result<Vec<_>>>()?;
+
+    // Round-trip check, outside the timed section
+    for (i, (person, message)) in people.iter().zip(&messages).enumerate() {
+        let decoded: Person = from_value(&table.decode(message)?)?;
+        if decoded != *person {
+            return Err(format!(
+                "Round-trip mismatch at record {}: expected {:?}, got {:?}",
+                i, person, decoded
+            )
+            .into());
+        }
+    }
+
+    let decode_stats = harness::measure(&options.harness, || {
+        for datum in &datums {
+            let _person: Person = from_value(&from_avro_datum(&schema, &mut &datum[..], None)?)?;
+        }
+        Ok(())
+    })?;
+    let decode_single_stats = harness::measure(&options.harness, || {
+        for message in &messages {
+            let _person: Person = from_value(&table.decode(message)?)?;
+        }
+        Ok(())
+    })?;
+
+    let datum_bytes: usize = datums.iter().map(Vec::len).sum();
+    let message_bytes: usize = messages.iter().map(Vec::len).sum();
+    let results: [(&str, usize, &Stats); 4] = [
+        ("single-object-baseline-encode", datum_bytes, &encode_stats),
+        ("single-object-encode", message_bytes, &encode_single_stats),
+        ("single-object-baseline-decode", datum_bytes, &decode_stats),
+        ("single-object-decode", message_bytes, &decode_single_stats),
+    ];
+    let records: Vec<ResultRecord> = results
+        .iter()
+        .map(|(operation, bytes, stats)| {
+            ResultRecord::measured(
+                ApacheBackend::IMPLEMENTATION,
+                operation,
+                people.len(),
+                *bytes as u64,
+                stats,
+            )
+        })
+        .collect();
+
+    reporter.emit(&records, || {
+        println!(
+            "=== Single-object Encoding ({} records, fingerprint {:016x}) ===",
+            people.len(),
+            fingerprint(&schema)
+        );
+        println!("Header matches apache-avro's GenericSingleObjectWriter");
+        println!("Unknown fingerprints are rejected: {}", rejection);
+        println!(
+            "Size: {} bytes as datums, {} as messages (+{:.1}%)",
+            datum_bytes,
+            message_bytes,
+            (message_bytes - datum_bytes) as f64 * 100.0 / datum_bytes.max(1) as f64
+        );
+        for pair in results.chunks(2) {
+            let (plain, single) = (&pair[0], &pair[1]);
+            println!(
+                "  {:<8} {:.6} seconds plain, {:.6} seconds single-object ({:+.1}%)",
+                plain
+                    .0
+                    .rsplit_once('-')
+                    .map_or(plain.0, |(_, direction)| direction),
+                plain.2.mean,
+                single.2.mean,
+                (single.2.mean / plain.2.mean - 1.0) * 100.0
+            );
+            print_stats(plain.2, people.len());
+            print_stats(single.2, people.len());
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,211 @@
+// Streaming vs full load vs early exit over a container file
+// Mirrors streaming_benchmark.ml: an Event container (deflate, a block every
+// 1000 records) is read three ways with apache-avro's Reader. Allocations are
+// always counted here, since peak heap growth is the point of the comparison.
+
+use crate::backend::ApacheBackend;
+use apache_avro::{from_value, Codec, Reader, Schema, Writer};
+use avro_rust_bench_common::cli::Options;
+use avro_rust_bench_common::harness::{self, HarnessConfig, Stats};
+use avro_rust_bench_common::report::{Reporter, ResultRecord};
+use avro_rust_bench_common::workloads::print_stats;
+use avro_rust_bench_common::{AvroBackend, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+const EVENT_SCHEMA: &str = r#"
+    {
+        "type": "record",
+        "name": "Event",
+        "fields": [
+            {"name": "timestamp", "type": "long"},
+            {"name": "user_id", "type": "int"},
+            {"name": "event_type", "type": "string"},
+            {"name": "value", "type": "int"}
+        ]
+    }
+"#;
+
+// Records per block, like Container_writer's ~sync_interval:1000
+const SYNC_INTERVAL: i32 = 1000;
+
+// Purchases collected by the early-exit pass
+const EARLY_EXIT_TARGET: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Event {
+    timestamp: i64,
+    user_id: i32,
+    event_type: String,
+    value: i32,
+}
+
+fn create_event(i: i32) -> Event {
+    Event {
+        timestamp: 1_609_459_200 + i64::from(i),
+        user_id: i % 1000,
+        event_type: match i % 3 {
+            0 => "click",
+            1 => "view",
+            _ => "purchase",
+        }
+        .to_string(),
+        value: i % 100,
+    }
+}
+
+fn create_test_file(path: &Path, count: i32) -> Result<()> {
+    let schema = Schema::parse_str(EVENT_SCHEMA)?;
+    let mut writer = Writer::with_codec(
+        &schema,
+        File::create(path)?,
+        Codec::Deflate(Default::default()),
+    );
+    for i in 0..count {
+        writer.append_ser(create_event(i))?;
+        if (i + 1) % SYNC_INTERVAL == 0 {
+            writer.flush()?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn open(path: &Path) -> Result<Reader<'static, BufReader<File>>> {
+    Ok(Reader::new(BufReader::new(File::open(path)?))?)
+}
+
+// Constant memory: one decoded record alive at a time
+fn iterate(path: &Path) -> Result<(usize, i64)> {
+    let mut count = 0;
+    let mut sum = 0;
+    for value in open(path)? {
+        let event: Event = from_value(&value?)?;
+        count += 1;
+        sum += i64::from(event.value);
+    }
+    Ok((count, sum))
+}
+
+// O(file size): every record is materialised before processing
+fn collect(path: &Path) -> Result<(usize, i64)> {
+    let events = open(path)?
+        .map(|value| Ok(from_value::<Event>(&value?)?))
+        .collect::<Result<Vec<_>>>()?;
+    let sum = events.iter().map(|event| i64::from(event.value)).sum();
+    Ok((events.len(), sum))
+}
+
+// Stops reading once enough purchases are found
+fn take_purchases(path: &Path) -> Result<(usize, usize)> {
+    let mut scanned = 0;
+    let purchases = open(path)?
+        .map(|value| {
+            scanned += 1;
+            Ok(from_value::<Event>(&value?)?)
+        })
+        .filter(|event: &Result<Event>| {
+            event
+                .as_ref()
+                .map_or(true, |event| event.event_type == "purchase")
+        })
+        .take(EARLY_EXIT_TARGET)
+        .collect::<Result<Vec<_>>>()?;
+    Ok((purchases.len(), scanned))
+}
+
+fn measure_with_result<T, F: FnMut() -> Result<T>>(
+    config: &HarnessConfig,
+    mut f: F,
+) -> Result<(T, Stats)> {
+    let mut result = None;
+    let stats = harness::measure(config, || {
+        result = Some(f()?);
+        Ok(())
+    })?;
+    Ok((result.expect("at least one iteration"), stats))
+}
+
+pub fn benchmark_streaming(options: &Options, reporter: &Reporter) -> Result<()> {
+    let config = HarnessConfig {
+        measure_alloc: true,
+        ..options.harness
+    };
+    let count = options.count;
+    let path = std::env::temp_dir().join(format!(
+        "bench_{}_streaming.avro",
+        ApacheBackend::IMPLEMENTATION
+    ));
+    create_test_file(&path, count)?;
+    let file_size = std::fs::metadata(&path)?.len();
+
+    let ((iterated, iterate_sum), iterate_stats) = measure_with_result(&config, || iterate(&path))?;
+    let ((collected, collect_sum), collect_stats) =
+        measure_with_result(&config, || collect(&path))?;
+    let ((purchases, scanned), take_stats) =
+        measure_with_result(&config, || take_purchases(&path))?;
+    std::fs::remove_file(&path)?;
+
+    if iterate_sum != collect_sum || iterated != collected {
+        return Err(format!(
+            "streaming and full load disagree: {} records (sum {}) vs {} records (sum {})",
+            iterated, iterate_sum, collected, collect_sum
+        )
+        .into());
+    }
+
+    let record = |operation, records, bytes, stats: &Stats| {
+        ResultRecord::measured(
+            ApacheBackend::IMPLEMENTATION,
+            operation,
+            records,
+            bytes,
+            stats,
+        )
+        .with_workload("event")
+        .with_codec("deflate")
+    };
+    // Early exit reads only part of the file; count its share of the bytes
+    let scanned_bytes = file_size * scanned as u64 / (count.max(1) as u64);
+    let records = [
+        record("stream-iterate", iterated, file_size, &iterate_stats),
+        record("stream-collect", collected, file_size, &collect_stats),
+        record("stream-take", scanned, scanned_bytes, &take_stats),
+    ];
+
+    reporter.emit(&records, || {
+        println!(
+            "Event file: {} records, {} bytes (deflate, {} records per block)",
+            count, file_size, SYNC_INTERVAL
+        );
+        println!();
+        println!("=== Streaming (Reader iteration) ===");
+        println!(
+            "Processed {} records in {:.6} seconds ({:.0} records/sec)",
+            iterated, iterate_stats.mean, records[0].records_per_sec
+        );
+        println!("Sum of values: {}", iterate_sum);
+        print_stats(&iterate_stats, iterated);
+        println!();
+        println!("=== Full Load (collect into Vec) ===");
+        println!(
+            "Loaded and processed {} records in {:.6} seconds ({:.0} records/sec)",
+            collected, collect_stats.mean, records[1].records_per_sec
+        );
+        println!("Sum of values: {}", collect_sum);
+        print_stats(&collect_stats, collected);
+        println!();
+        println!("=== Early Exit (first {} purchases) ===", EARLY_EXIT_TARGET);
+        println!(
+            "Found {} purchases in {:.6} seconds, scanned {} records ({:.1}% of file)",
+            purchases,
+            take_stats.mean,
+            scanned,
+            scanned as f64 * 100.0 / count.max(1) as f64
+        );
+        print_stats(&take_stats, scanned);
+    });
+    Ok(())
+}
@@ -0,0 +1,130 @@
+// Cross-language container verification
+// Opens a Person container file written by any implementation (avro-simple's
+// Container_writer, Java, Python or the Rust benches), decodes every record
+// into Person and checks it against create_person(i). The header is parsed
+// with the container module so the codec and every metadata entry can be
+// reported.
+
+use crate::container::read_file_metadata;
+use apache_avro::{from_value, Reader, Schema};
+use avro_rust_bench_common::{create_person, Person, Result, PERSON_SCHEMA};
+use std::fs::File;
+use std::io::BufReader;
+
+// Listing every divergent record of a large file is not useful
+const MAX_REPORTED_MISMATCHES: usize = 10;
+
+pub fn verify(path: &str) -> Result<()> {
+    let metadata = read_file_metadata(path)?;
+
+    let reader = Reader::new(BufReader::new(File::open(path)?))?;
+    let writer_schema = reader.writer_schema().clone();
+    let person_schema = Schema::parse_str(PERSON_SCHEMA)?;
+
+    println!("File:   {}", path);
+    println!("Schema: {}", writer_schema.canonical_form());
+    if writer_schema.canonical_form() != person_schema.canonical_form() {
+        println!("        (differs from the benchmark Person schema)");
+    }
+    println!(
+        "Codec:  {}",
+        metadata
+            .get("avro.codec")
+            .map(|codec| String::from_utf8_lossy(codec).into_owned())
+            .unwrap_or_else(|| "null".to_string())
+    );
+    println!("Metadata:");
+    let mut keys: Vec<&String> = metadata.keys().collect();
+    keys.sort();
+    for key in keys {
+        // The schema is already printed above
+        if key != "avro.schema" {
+            println!("  {} = {}", key, describe_bytes(&metadata[key]));
+        }
+    }
+
+    let mut records = 0;
+    let mut mismatches = 0;
+    for (i, value) in reader.enumerate() {
+        let value = value?;
+        records += 1;
+        let expected = create_person(i as i32);
+        let problem = match from_value::<Person>(&value) {
+            Ok(person) if person == expected => continue,
+            Ok(person) => describe_mismatch(&expected, &person),
+            Err(err) => format!("does not decode as Person: {}", err),
+        };
+        mismatches += 1;
+        if mismatches <= MAX_REPORTED_MISMATCHES {
+            println!("Mismatch at record {}: {}", i, problem);
+        }
+    }
+    if mismatches > MAX_REPORTED_MISMATCHES {
+        println!(
+            "... {} more mismatches not shown",
+            mismatches - MAX_REPORTED_MISMATCHES
+        );
+    }
+
+    println!("Records: {} read, {} mismatched", records, mismatches);
+    if mismatches > 0 {
+        return Err(format!(
+            "{} of {} records diverge from create_person",
+            mismatches, records
+        )
+        .into());
+    }
+    Ok(())
+}
+
+// Binary values show their first bytes only
+const MAX_HEX_BYTES: usize = 32;
+
+pub(crate) fn describe_bytes(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => format!("{:?}", text),
+        Err(_) => format!(
+            "0x{}{} ({} bytes)",
+            bytes
+                .iter()
+                .take(MAX_HEX_BYTES)
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>(),
+            if bytes.len() > MAX_HEX_BYTES {
+                "..."
+            } else {
+                ""
+            },
+            bytes.len()
+        ),
+    }
+}
+
+fn describe_mismatch(expected: &Person, actual: &Person) -> String {
+    let mut fields = Vec::new();
+    if expected.name != actual.name {
+        fields.push(format!(
+            "name: got {:?}, expected {:?}",
+            actual.name, expected.name
+        ));
+    }
+    if expected.age != actual.age {
+        fields.push(format!(
+            "age: got {}, expected {}",
+            actual.age, expected.age
+        ));
+    }
+    if expected.email != actual.email {
+        fields.push(format!(
+            "email: got {:?}, expected {:?}",
+            actual.email, expected.email
+        ));
+    }
+    if expected.phone_numbers != actual.phone_numbers {
+        fields.push(format!(
+            "phone_numbers: got {:?}, expected {:?}",
+            actual.phone_numbers, expected.phone_numbers
+        ));
+    }
+    fields.join(", ")
+}